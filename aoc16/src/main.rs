@@ -0,0 +1,293 @@
+extern crate day01;
+extern crate day02;
+extern crate day03;
+extern crate day04;
+extern crate day05;
+extern crate day06;
+extern crate day07;
+extern crate day08;
+extern crate day09;
+extern crate day10;
+extern crate day11;
+extern crate day12;
+extern crate day13;
+extern crate day14;
+extern crate day15;
+extern crate day16;
+extern crate day17;
+extern crate day18;
+extern crate day19;
+extern crate day20;
+extern crate day21;
+extern crate day22;
+extern crate day23;
+extern crate day24;
+extern crate day25;
+
+use std::env;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::process;
+use std::time::Instant;
+
+const USAGE: &'static str = "Usage: aoc16 run <day> [--part 1|2] [--input <path|->]
+       aoc16 run all [--part 1|2] [--input-dir <dir>]
+
+A single day reads its input from the given file, or from stdin if no path (or '-') is given.
+'run all' reads dayNN.txt for each day from the input directory (default: inputs).
+--part only picks which answer is printed. Each day works out both parts together, so both are
+still solved, and the time shown covers both.";
+
+const DEFAULT_INPUT_DIR: &'static str = "inputs";
+
+#[derive(Debug, PartialEq)]
+enum Days {
+    One(u8),
+    All
+}
+
+#[derive(Debug, PartialEq)]
+struct RunArgs {
+    days: Days,
+    part: Option<u8>,
+    input: Option<String>,
+    input_dir: Option<String>
+}
+
+impl RunArgs {
+    fn parse(args: &[String]) -> Result<RunArgs, String> {
+        let mut args = args.iter();
+
+        match args.next().map(|s| s.as_str()) {
+            Some("run") => {},
+            Some(other) => return Err(format!("Unknown command: {}", other)),
+            None => return Err("No command given".to_string())
+        }
+
+        let days = match args.next().map(|s| s.as_str()) {
+            Some("all") => Days::All,
+            Some(day) => match day.parse::<u8>() {
+                Ok(d) if (1..26).contains(&d) => Days::One(d),
+                _ => return Err(format!("Day must be 1-25 or 'all', but got: {}", day))
+            },
+            None => return Err("No day given".to_string())
+        };
+
+        let mut part = None;
+        let mut input = None;
+        let mut input_dir = None;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--part" => {
+                    part = match args.next().map(|s| s.as_str()) {
+                        Some("1") => Some(1),
+                        Some("2") => Some(2),
+                        Some(other) => return Err(format!("Part must be 1 or 2, but got: {}", other)),
+                        None => return Err("No part given after --part".to_string())
+                    };
+                },
+                "--input" => {
+                    if days == Days::All {
+                        return Err("--input only works with a single day, use --input-dir for 'all'".to_string());
+                    }
+                    input = match args.next() {
+                        Some(path) => Some(path.clone()),
+                        None => return Err("No path given after --input".to_string())
+                    };
+                },
+                "--input-dir" => {
+                    if days != Days::All {
+                        return Err("--input-dir only works with 'all', use --input for a single day".to_string());
+                    }
+                    input_dir = match args.next() {
+                        Some(dir) => Some(dir.clone()),
+                        None => return Err("No directory given after --input-dir".to_string())
+                    };
+                },
+                other => return Err(format!("Unexpected argument: {}", other))
+            }
+        }
+
+        Ok(RunArgs {
+            days: days,
+            part: part,
+            input: input,
+            input_dir: input_dir
+        })
+    }
+}
+
+// Both answers have already been worked out by now, the part only decides which are printed
+fn print_answers<A: Display, B: Display>((part1, part2): (A, B), part: Option<u8>) {
+    if part != Some(2) {
+        print_answer(1, part1);
+    }
+    if part != Some(1) {
+        print_answer(2, part2);
+    }
+}
+
+// For days which can fail, e.g. on a malformed input or an assembunny program which overflows
+fn print_parsed_answers<A: Display, B: Display, E: Display>(answers: Result<(A, B), E>, part: Option<u8>) {
+    match answers {
+        Ok(answers) => print_answers(answers, part),
+        Err(error) => println!("  Couldn't solve: {}", error)
+    }
+}
+
+fn print_answer<T: Display>(part: u8, answer: T) {
+    let answer = answer.to_string();
+    if answer.contains('\n') {
+        // Multi-line answers (e.g. rendered screens) read better starting on their own line
+        println!("  Part {}:", part);
+        for line in answer.lines() {
+            println!("    {}", line);
+        }
+    } else {
+        println!("  Part {}: {}", part, answer);
+    }
+}
+
+fn read_input(path: Option<&str>) -> Result<String, String> {
+    let mut input = String::new();
+    match path {
+        None | Some("-") => {
+            io::stdin().read_to_string(&mut input)
+                .map_err(|e| format!("Couldn't read input from stdin: {}", e))?;
+        },
+        Some(path) => {
+            File::open(path)
+                .and_then(|mut file| file.read_to_string(&mut input))
+                .map_err(|e| format!("Couldn't read input from {}: {}", path, e))?;
+        }
+    }
+    Ok(input)
+}
+
+fn run_day(day: u8, input: &str, part: Option<u8>) {
+    println!("Day {}", day);
+
+    let start = Instant::now();
+    match day {
+        1 => print_parsed_answers(day01::solve(input), part),
+        2 => print_answers(day02::solve(input), part),
+        3 => print_parsed_answers(day03::solve(input), part),
+        4 => print_parsed_answers(day04::solve(input), part),
+        5 => print_answers(day05::solve(input), part),
+        6 => print_answers(day06::solve(input), part),
+        7 => print_answers(day07::solve(input), part),
+        8 => print_answers(day08::solve(input), part),
+        9 => print_answers(day09::solve(input), part),
+        10 => print_parsed_answers(day10::solve(input), part),
+        11 => print_parsed_answers(day11::solve(input), part),
+        12 => print_parsed_answers(day12::solve(input), part),
+        13 => print_answers(day13::solve(input), part),
+        14 => print_answers(day14::solve(input), part),
+        15 => print_answers(day15::solve(input), part),
+        16 => print_answers(day16::solve(input), part),
+        17 => print_answers(day17::solve(input), part),
+        18 => print_answers(day18::solve(input), part),
+        19 => print_answers(day19::solve(input), part),
+        20 => print_answers(day20::solve(input), part),
+        21 => print_answers(day21::solve(input), part),
+        22 => print_parsed_answers(day22::solve(input), part),
+        23 => print_parsed_answers(day23::solve(input), part),
+        24 => print_parsed_answers(day24::solve(input), part),
+        25 => print_parsed_answers(day25::solve(input), part),
+        _ => unreachable!("Day {} should have been rejected when parsing arguments", day)
+    }
+    let elapsed = start.elapsed();
+
+    println!("  ({}ms)", elapsed.as_secs() * 1000 + elapsed.subsec_nanos() as u64 / 1000000);
+}
+
+fn main() {
+    let args:Vec<String> = env::args().skip(1).collect();
+
+    let run_args = match RunArgs::parse(&args) {
+        Ok(run_args) => run_args,
+        Err(message) => {
+            eprintln!("{}", message);
+            eprintln!("{}", USAGE);
+            process::exit(1);
+        }
+    };
+
+    match run_args.days {
+        Days::One(day) => {
+            let input = read_input(run_args.input.as_deref()).unwrap_or_else(|message| {
+                eprintln!("{}", message);
+                process::exit(1);
+            });
+            run_day(day, &input, run_args.part);
+        },
+        Days::All => {
+            let dir = run_args.input_dir.unwrap_or_else(|| DEFAULT_INPUT_DIR.to_string());
+            for day in 1..26 {
+                let path = Path::new(&dir).join(format!("day{:02}.txt", day));
+                match read_input(Some(&path.to_string_lossy())) {
+                    Ok(input) => run_day(day, &input, run_args.part),
+                    Err(message) => {
+                        // Carry on with the other days, an account may not have every input yet
+                        println!("Day {}", day);
+                        println!("  Skipped: {}", message);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+fn to_args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn run_with_day_and_part_parses() {
+    assert_eq!(
+        RunArgs::parse(&to_args(&["run", "10", "--part", "2"])),
+        Ok(RunArgs { days: Days::One(10), part: Some(2), input: None, input_dir: None })
+    );
+}
+
+#[test]
+fn run_all_without_part_parses() {
+    assert_eq!(
+        RunArgs::parse(&to_args(&["run", "all"])),
+        Ok(RunArgs { days: Days::All, part: None, input: None, input_dir: None })
+    );
+}
+
+#[test]
+fn out_of_range_day_is_rejected() {
+    assert!(RunArgs::parse(&to_args(&["run", "26"])).is_err());
+    assert!(RunArgs::parse(&to_args(&["run", "0"])).is_err());
+}
+
+#[test]
+fn bad_part_is_rejected() {
+    assert!(RunArgs::parse(&to_args(&["run", "1", "--part", "3"])).is_err());
+    assert!(RunArgs::parse(&to_args(&["run", "1", "--part"])).is_err());
+}
+
+#[test]
+fn input_path_parses_for_a_single_day() {
+    assert_eq!(
+        RunArgs::parse(&to_args(&["run", "3", "--input", "day03.txt"])),
+        Ok(RunArgs { days: Days::One(3), part: None, input: Some("day03.txt".to_string()), input_dir: None })
+    );
+    assert!(RunArgs::parse(&to_args(&["run", "3", "--input"])).is_err());
+}
+
+#[test]
+fn input_dir_only_works_with_all() {
+    assert_eq!(
+        RunArgs::parse(&to_args(&["run", "all", "--input-dir", "mine"])),
+        Ok(RunArgs { days: Days::All, part: None, input: None, input_dir: Some("mine".to_string()) })
+    );
+    assert!(RunArgs::parse(&to_args(&["run", "3", "--input-dir", "mine"])).is_err());
+    assert!(RunArgs::parse(&to_args(&["run", "all", "--input", "day03.txt"])).is_err());
+}
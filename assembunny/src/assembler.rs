@@ -0,0 +1,358 @@
+use std::collections::HashMap;
+
+use parse_error::ParseError;
+
+use ::parser::{DEFAULT_REGISTERS, Instruction, Operand};
+
+// A friendlier way to write assembunny. On top of the plain instructions, which still work as is:
+//
+//   ; comments run to the end of a line
+//   .const COUNT 643        named constants, usable wherever a number is
+//   loop: dec c             labels, which jnz (or tgl) can use instead of counting lines
+//         jnz c loop
+//   .macro add to from      macros with parameters, expanded wherever they're used by name.
+//   again: inc to           Labels inside a macro are local to each use of it.
+//          dec from
+//          jnz from again
+//   .end
+//         add a b
+//
+// Everything is lowered to plain instructions, so the result runs (and optimises) like any other
+// program.
+
+// Macros can use other macros, but not without end
+const MAX_MACRO_DEPTH: usize = 20;
+
+struct Macro {
+    params: Vec<String>,
+    // (line number, line) for each line of the body
+    body: Vec<(usize, String)>
+}
+
+// One instruction after macros have been expanded, remembering where it came from for errors
+struct Statement {
+    line_num: usize,
+    words: Vec<String>,
+    // The column and text of each word as it was written, before any macro parameters were replaced
+    sources: Vec<(usize, String)>
+}
+
+impl Statement {
+    fn error(&self, word: usize, message: &str) -> ParseError {
+        let (column, ref text) = self.sources[word];
+        ParseError::new(self.line_num, column, text, message)
+    }
+}
+
+fn is_register(name: &str, num_registers: usize) -> bool {
+    matches!(Operand::parse_with_registers(name, num_registers), Ok(Operand::Register(_)))
+}
+
+// Labels, constants, macros and their parameters are named like Rust identifiers, except for the
+// registers' names
+fn is_name(name: &str, num_registers: usize) -> bool {
+    let mut chars = name.chars();
+    let starts_well = match chars.next() {
+        Some(c) => c.is_alphabetic() || c == '_',
+        None => false
+    };
+    starts_well && chars.all(|c| c.is_alphanumeric() || c == '_') && !is_register(name, num_registers)
+}
+
+// Which of the words, joined up with spaces, the column falls in
+fn word_at(words: &[String], column: usize) -> usize {
+    let mut end = 0;
+    for (i, word) in words.iter().enumerate() {
+        end += word.chars().count() + 1;
+        if column <= end {
+            return i;
+        }
+    }
+    words.len() - 1
+}
+
+// The column a slice of the line starts at
+fn column_of(line: &str, word: &str) -> usize {
+    let offset = word.as_ptr() as usize - line.as_ptr() as usize;
+    line[..offset].chars().count() + 1
+}
+
+fn strip_comment(line: &str) -> &str {
+    match line.find(';') {
+        Some(idx) => &line[..idx],
+        None => line
+    }
+}
+
+// Splits `label: rest` into the label and the rest of the line
+fn split_label(text: &str) -> (Option<&str>, &str) {
+    let trimmed = text.trim();
+    match trimmed.find(':') {
+        Some(idx) => (Some(trimmed[..idx].trim()), trimmed[idx + 1..].trim()),
+        None => (None, trimmed)
+    }
+}
+
+struct Assembler {
+    num_registers: usize,
+    constants: HashMap<String, i64>,
+    macros: HashMap<String, Macro>,
+    labels: HashMap<String, usize>,
+    statements: Vec<Statement>,
+    expansions: usize
+}
+
+impl Assembler {
+    fn new(num_registers: usize) -> Assembler {
+        Assembler {
+            num_registers: num_registers,
+            constants: HashMap::new(),
+            macros: HashMap::new(),
+            labels: HashMap::new(),
+            statements: vec![],
+            expansions: 0
+        }
+    }
+
+    // Reads the constant and macro definitions, and expands everything else into statements
+    fn read(&mut self, source: &str) -> Result<(), ParseError> {
+        let mut lines = source.lines().enumerate().map(|(i, line)| (i + 1, line));
+
+        while let Some((line_num, line)) = lines.next() {
+            let text = strip_comment(line);
+            let words:Vec<&str> = text.split_whitespace().collect();
+
+            match words.first() {
+                Some(&".const") => {
+                    if words.len() != 3 {
+                        return Err(ParseError::new(line_num, 1, line, "Expected .const <name> <value>"));
+                    }
+                    let name = words[1];
+                    if !is_name(name, self.num_registers) || self.constants.contains_key(name) {
+                        return Err(ParseError::at(line_num, line, name, "Expected a new name for the constant"));
+                    }
+                    let value = self.constant_value(words[2])
+                        .ok_or_else(|| ParseError::at(line_num, line, words[2], "Expected a number or constant"))?;
+                    self.constants.insert(name.to_string(), value);
+                },
+                Some(&".macro") => {
+                    let name = match words.get(1) {
+                        Some(name) if is_name(name, self.num_registers) && !self.macros.contains_key(*name) => name.to_string(),
+                        _ => return Err(ParseError::new(line_num, 1, line, "Expected a new name for the macro"))
+                    };
+                    let params:Vec<String> = words[2..].iter().map(|param| param.to_string()).collect();
+                    if let Some(param) = params.iter().find(|param| !is_name(param, self.num_registers)) {
+                        return Err(ParseError::at(line_num, line, param, "Macro parameters can't be registers or numbers"));
+                    }
+
+                    let mut body = vec![];
+                    loop {
+                        match lines.next() {
+                            Some((_, body_line)) if strip_comment(body_line).trim() == ".end" => break,
+                            Some((body_line_num, body_line)) => body.push((body_line_num, body_line.to_string())),
+                            None => return Err(ParseError::new(line_num, 1, line, "Macro has no .end"))
+                        }
+                    }
+                    self.macros.insert(name, Macro { params: params, body: body });
+                },
+                Some(&".end") => return Err(ParseError::new(line_num, 1, line, ".end without a .macro")),
+                _ => self.statement(line_num, line, text, &HashMap::new(), 0)?
+            }
+        }
+        Ok(())
+    }
+
+    fn constant_value(&self, word: &str) -> Option<i64> {
+        word.parse::<i64>().ok().or_else(|| self.constants.get(word).cloned())
+    }
+
+    // Adds a line of the program (or of a macro being expanded), where `renames` gives the values
+    // of macro parameters and the local names of labels in the macro. text must be a slice of line,
+    // so the words in it can be found again for errors
+    fn statement(&mut self, line_num: usize, line: &str, text: &str, renames: &HashMap<String, String>, depth: usize)
+            -> Result<(), ParseError> {
+        let (label, rest) = split_label(text);
+        if let Some(label) = label {
+            if !is_name(label, self.num_registers) {
+                return Err(ParseError::at(line_num, line, label, "Labels can't be registers or numbers"));
+            }
+            let label = renames.get(label).map_or(label.to_string(), |name| name.clone());
+            if self.labels.contains_key(&label) {
+                return Err(ParseError::at(line_num, line, &label, "Label is already used"));
+            }
+            self.labels.insert(label, self.statements.len());
+        }
+
+        let words:Vec<String> = rest.split_whitespace()
+            .map(|word| renames.get(word).map_or(word.to_string(), |name| name.clone()))
+            .collect();
+        if words.is_empty() {
+            return Ok(());
+        }
+        let sources = rest.split_whitespace().map(|word| (column_of(line, word), word.to_string())).collect();
+
+        if self.macros.contains_key(&words[0]) {
+            self.expand(line_num, line, &words, depth)
+        } else {
+            self.statements.push(Statement { line_num: line_num, words: words, sources: sources });
+            Ok(())
+        }
+    }
+
+    fn expand(&mut self, line_num: usize, line: &str, words: &[String], depth: usize) -> Result<(), ParseError> {
+        if depth >= MAX_MACRO_DEPTH {
+            return Err(ParseError::at(line_num, line, &words[0], "Macros are nested too deeply"));
+        }
+
+        let (params, body) = {
+            let mac = &self.macros[&words[0]];
+            (mac.params.clone(), mac.body.clone())
+        };
+        if words.len() - 1 != params.len() {
+            let message = format!("Macro takes {} arguments, but was given {}", params.len(), words.len() - 1);
+            return Err(ParseError::at(line_num, line, &words[0], &message));
+        }
+
+        // Each use of the macro gets its own copies of the labels inside it
+        self.expansions += 1;
+        let mut renames:HashMap<String, String> = params.into_iter().zip(words[1..].iter().cloned()).collect();
+        for (_, body_line) in &body {
+            if let (Some(label), _) = split_label(strip_comment(body_line)) {
+                renames.insert(label.to_string(), format!("{}.{}", label, self.expansions));
+            }
+        }
+
+        for (body_line_num, body_line) in body {
+            self.statement(body_line_num, &body_line, strip_comment(&body_line), &renames, depth + 1)?;
+        }
+        Ok(())
+    }
+
+    // Turns the statements into instructions, now every label is known
+    fn lower(&self) -> Result<Vec<Instruction>, ParseError> {
+        self.statements.iter().enumerate()
+            .map(|(pc, statement)| {
+                let mut words = vec![statement.words[0].clone()];
+                for (i, word) in statement.words.iter().enumerate().skip(1) {
+                    let resolved = if Operand::parse_with_registers(word, self.num_registers).is_ok() {
+                        word.clone()
+                    } else if let Some(value) = self.constants.get(word) {
+                        value.to_string()
+                    } else if let Some(&target) = self.labels.get(word) {
+                        (target as i32 - pc as i32).to_string()
+                    } else {
+                        return Err(statement.error(i, "Unknown label or constant"));
+                    };
+                    words.push(resolved);
+                }
+
+                // The error points into the instruction with its labels and constants resolved, so
+                // point at the word in the source it came from instead
+                Instruction::parse_with_registers(statement.line_num, &words.join(" "), self.num_registers)
+                    .map_err(|e| statement.error(word_at(&words, e.column), &e.message))
+            })
+            .collect()
+    }
+}
+
+pub fn assemble(source: &str) -> Result<Vec<Instruction>, ParseError> {
+    assemble_with_registers(source, DEFAULT_REGISTERS)
+}
+
+// Lets the program use the first num_registers letters as registers, like parse_with_registers
+pub fn assemble_with_registers(source: &str, num_registers: usize) -> Result<Vec<Instruction>, ParseError> {
+    let mut assembler = Assembler::new(num_registers);
+    assembler.read(source)?;
+    assembler.lower()
+}
+
+#[cfg(test)]
+use ::parser::{parse, parse_with_registers};
+
+#[test]
+fn labels_become_offsets() {
+    let source = r"
+        cpy 3 b
+outer:  cpy b c
+inner:  inc a       ; a += c
+        dec c
+        jnz c inner
+        dec d
+        jnz d outer
+        jnz 1 done
+        inc b
+done:";
+    let expected = r"cpy 3 b
+cpy b c
+inc a
+dec c
+jnz c -2
+dec d
+jnz d -5
+jnz 1 2
+inc b";
+    assert_eq!(assemble(source), parse(expected));
+}
+
+#[test]
+fn constants_and_macros_with_local_labels_are_expanded() {
+    let source = r".const COUNT 4
+.const START COUNT
+.macro move to from
+again:  inc to
+        dec from
+        jnz from again
+.end
+        cpy START b
+        move a b
+        cpy COUNT c
+        move d c";
+    let expected = r"cpy 4 b
+inc a
+dec b
+jnz b -2
+cpy 4 c
+inc d
+dec c
+jnz c -2";
+    assert_eq!(assemble(source), parse(expected));
+}
+
+#[test]
+fn unknown_names_are_reported_where_they_are_used() {
+    let source = "loop: dec a\n      jnz a lop";
+    assert_eq!(assemble(source).unwrap_err(), ParseError::new(2, 13, "lop", "Unknown label or constant"));
+}
+
+#[test]
+fn disassembled_programs_assemble_back_to_the_same_instructions() {
+    let program = r"cpy a b
+dec b
+cpy b c
+inc a
+dec c
+jnz c -2
+tgl c
+jnz 1 c
+jnz 0 0
+jnz a -8
+jnz 1 3";
+    let instructions = parse(program).unwrap();
+    let mut disassembled = vec![];
+    ::disassembler::disassemble(&instructions, &mut disassembled).unwrap();
+    assert_eq!(assemble(&String::from_utf8(disassembled).unwrap()), Ok(instructions));
+}
+
+#[test]
+fn extra_registers_can_be_used_and_arent_names() {
+    let source = "f: inc f\n   jnz f f";
+    assert_eq!(assemble_with_registers(source, 6).unwrap_err(), ParseError::new(1, 1, "f", "Labels can't be registers or numbers"));
+    assert_eq!(assemble_with_registers("inc f\njnz e f", 6), parse_with_registers("inc f\njnz e f", 6));
+}
+
+#[test]
+fn bad_operands_are_reported_where_they_are_written() {
+    // The label resolves to 2, which is what jnz would otherwise be blamed for
+    let source = "      jnz 1 done\n      tgl a\ndone: in done";
+    assert_eq!(assemble(source).unwrap_err(), ParseError::new(3, 10, "done", "Expected a register (a-d) to read into"));
+}
@@ -0,0 +1,104 @@
+use ::optimiser::run_loop;
+use ::parser::Instruction;
+use ::parser::Instruction::*;
+use ::parser::Operand::*;
+
+// Rather than matching on every instruction and its operands each time it runs, each instruction
+// is turned into a closure once, up front, with its registers and literals already worked out.
+// Running the program is then just calling the closure at the program counter.
+//
+// Compiled code can't be toggled, so a `tgl` which hits the program hands back to the interpreter.
+
+// What the CPU should do after running a compiled instruction
+pub enum Control {
+    // Move the program counter by this much
+    Jump(i64),
+    // Pass the value to the execution environment, then move on
+    Output(i64),
+    // Read a value from the execution environment into this register, then move on
+    Input(usize),
+    // Stop here, and let the interpreter run the `tgl` and everything after it
+    Toggle,
+    // Stop here, as the instruction would overflow a register. The registers are left as they were.
+    Overflow
+}
+
+pub type CompiledInstruction = Box<dyn Fn(&mut [i64]) -> Control>;
+
+// Compiles the program as it will be run (optimised or not). Nops swallowed by an optimised loop
+// get the original instruction, in case something jumps into the middle of the loop.
+pub fn compile(instructions: &[Instruction], unoptimised_instructions: &[Instruction]) -> Vec<CompiledInstruction> {
+    instructions.iter()
+        .zip(unoptimised_instructions.iter())
+        .enumerate()
+        .map(|(pc, (&instr, &unoptimised))| {
+            let instr = if instr == Nop { unoptimised } else { instr };
+            compile_instruction(instr, pc as i32, instructions.len() as i32)
+        })
+        .collect()
+}
+
+fn compile_instruction(instr: Instruction, pc: i32, len: i32) -> CompiledInstruction {
+    match instr {
+        Copy{ source: Register(source), target: Register(target) } => Box::new(move |regs| {
+            regs[target] = regs[source];
+            Control::Jump(1)
+        }),
+        Copy{ source: Literal(value), target: Register(target) } => Box::new(move |regs| {
+            regs[target] = value;
+            Control::Jump(1)
+        }),
+        Inc{ reg: Register(reg_idx) } => Box::new(move |regs| {
+            match regs[reg_idx].checked_add(1) {
+                Some(value) => {
+                    regs[reg_idx] = value;
+                    Control::Jump(1)
+                },
+                None => Control::Overflow
+            }
+        }),
+        Dec{ reg: Register(reg_idx) } => Box::new(move |regs| {
+            match regs[reg_idx].checked_sub(1) {
+                Some(value) => {
+                    regs[reg_idx] = value;
+                    Control::Jump(1)
+                },
+                None => Control::Overflow
+            }
+        }),
+        JumpNotZero{ check: Literal(0), .. } => Box::new(|_| Control::Jump(1)),
+        JumpNotZero{ check: Literal(_), delta: Literal(delta) } => Box::new(move |_| Control::Jump(delta)),
+        JumpNotZero{ check: Literal(_), delta: Register(delta) } => Box::new(move |regs| Control::Jump(regs[delta])),
+        JumpNotZero{ check: Register(check), delta: Literal(delta) } => Box::new(move |regs| {
+            Control::Jump(if regs[check] != 0 { delta } else { 1 })
+        }),
+        JumpNotZero{ check: Register(check), delta: Register(delta) } => Box::new(move |regs| {
+            Control::Jump(if regs[check] != 0 { regs[delta] } else { 1 })
+        }),
+        Toggle{ reg } => Box::new(move |regs| {
+            let offset = match reg {
+                Register(reg_idx) => regs[reg_idx],
+                Literal(value) => value
+            };
+            // Toggling something outside the program does nothing, so needn't stop compiled code
+            let idx = (pc as i64).saturating_add(offset);
+            if idx >= 0 && idx < len as i64 {
+                Control::Toggle
+            } else {
+                Control::Jump(1)
+            }
+        }),
+        Out{ operand: Register(reg_idx) } => Box::new(move |regs| Control::Output(regs[reg_idx])),
+        Out{ operand: Literal(value) } => Box::new(move |_| Control::Output(value)),
+        In{ reg: Register(reg_idx) } => Box::new(move |_| Control::Input(reg_idx)),
+        Loop{ counter, effects, len } => Box::new(move |regs| {
+            match run_loop(counter, &effects, regs) {
+                Some(()) => Control::Jump(len as i64),
+                None => Control::Overflow
+            }
+        }),
+        // Toggling can produce nonsense like `cpy 1 2` or `inc 3`, which is skipped
+        Copy{ target: Literal(_), .. } | Inc{ reg: Literal(_) } | Dec{ reg: Literal(_) } | In{ reg: Literal(_) } => Box::new(|_| Control::Jump(1)),
+        Nop => unreachable!()
+    }
+}
@@ -0,0 +1,666 @@
+use ::parser::Instruction;
+use ::parser::Instruction::*;
+use ::parser::Operand;
+use ::parser::Operand::*;
+
+use compiler::{CompiledInstruction, Control, compile};
+use optimiser::{optimise, run_loop};
+use profiler::Profile;
+use trace::{TraceEvent, TraceStart, TraceStep, Toggled, Tracer};
+
+use parse_error::ParseError;
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Write};
+
+// Hooks for the outside world: where `out` values go, where `in` values come from, and when to stop
+// a program which may never finish on its own
+pub trait ExecutionEnvironment<T> {
+    fn handle_output(&mut self, val: i64);
+    // The value for an `in` instruction, or None if there is no more input, which ends the program
+    fn handle_input(&mut self) -> Option<i64> {
+        None
+    }
+    fn should_terminate(&mut self, registers: &[i64], program_counter: usize) -> Option<T>;
+}
+
+// Ignores any output, and lets the program run until it jumps out of the instruction list
+pub struct RunToEnd {}
+
+impl ExecutionEnvironment<()> for RunToEnd {
+    fn handle_output(&mut self, _: i64) {}
+
+    fn should_terminate(&mut self, _: &[i64], _: usize) -> Option<()> {
+        None
+    }
+}
+
+// An instruction whose result doesn't fit in a register. It isn't run, so the registers are left as
+// they were and the program counter still points at it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Overflow {
+    pub pc: i32,
+    // As it would be shown, which for an optimised loop is what the loop does
+    pub instruction: String
+}
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Overflow at {}: {}", self.pc, self.instruction)
+    }
+}
+
+impl Error for Overflow {}
+
+// Why a program's source didn't give an answer: it either didn't parse, or overflowed when run
+#[derive(Debug, Clone, PartialEq)]
+pub enum RunError {
+    Parse(ParseError),
+    Overflow(Overflow)
+}
+
+impl From<ParseError> for RunError {
+    fn from(error: ParseError) -> RunError {
+        RunError::Parse(error)
+    }
+}
+
+impl From<Overflow> for RunError {
+    fn from(overflow: Overflow) -> RunError {
+        RunError::Overflow(overflow)
+    }
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RunError::Parse(ref error) => write!(f, "{}", error),
+            RunError::Overflow(ref overflow) => write!(f, "{}", overflow)
+        }
+    }
+}
+
+impl Error for RunError {}
+
+// What happened when the CPU executed a single instruction
+#[derive(Debug, PartialEq)]
+pub enum Step<T> {
+    Running,
+    Finished,
+    Terminated(T),
+    Overflowed(Overflow)
+}
+
+// Shows registers the way the debugger and friends do, e.g. "a=1 b=0 c=0 d=7"
+pub fn format_registers(regs: &[i64]) -> String {
+    regs.iter().enumerate()
+        .map(|(reg_idx, value)| format!("{}={}", Register(reg_idx), value))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+// How many registers the program needs, going by the last one it uses
+fn registers_used(instructions: &[Instruction]) -> usize {
+    let highest = |operand| match operand {
+        Register(reg_idx) => reg_idx + 1,
+        Literal(_) => 0
+    };
+    instructions.iter()
+        .map(|instr| match *instr {
+            Copy{ source: a, target: b } | JumpNotZero{ check: a, delta: b } => highest(a).max(highest(b)),
+            Inc{ reg: a } | Dec{ reg: a } | Toggle{ reg: a } | Out{ operand: a } | In{ reg: a } => highest(a),
+            Loop{ .. } | Nop => 0
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+// Where a jump by delta from pc lands. Anything too far away to fit is well outside the program.
+fn jump(pc: i32, delta: i64) -> i32 {
+    (pc as i64).saturating_add(delta).max(-1).min(i32::MAX as i64) as i32
+}
+
+pub struct Cpu<T> {
+    // As many registers as were given, or as the program uses if that's more
+    pub regs: Vec<i64>,
+    pc: i32,
+    unoptimised_instructions: Vec<Instruction>,
+    instructions: Vec<Instruction>,
+    optimised: bool,
+    input_exhausted: bool,
+    compiled: bool,
+    compiled_instructions: Option<Vec<CompiledInstruction>>,
+    exec_env: Box<dyn ExecutionEnvironment<T>>,
+    tracer: Option<Tracer>,
+    profiled: bool,
+    profile: Option<Profile>
+}
+
+impl Cpu<()> {
+    pub fn new(regs: &[i64]) -> Cpu<()> {
+        Cpu::with_env(regs, Box::new(RunToEnd {}))
+    }
+}
+
+impl<T> Cpu<T> {
+    pub fn with_env(regs: &[i64], exec_env: Box<dyn ExecutionEnvironment<T>>) -> Cpu<T> {
+        Cpu {
+            regs: regs.to_vec(),
+            pc: 0,
+            unoptimised_instructions: vec![],
+            instructions: vec![],
+            optimised: true,
+            input_exhausted: false,
+            compiled: false,
+            compiled_instructions: None,
+            exec_env: exec_env,
+            tracer: None,
+            profiled: false,
+            profile: None
+        }
+    }
+
+    // Whether to replace loops with the optimised instructions. Takes effect on the next load.
+    pub fn set_optimised(&mut self, optimised: bool) {
+        self.optimised = optimised;
+    }
+
+    // Whether `process` runs the program as compiled closures until a tgl modifies it, rather than
+    // interpreting it. Takes effect on the next load, and is ignored while tracing or profiling.
+    pub fn set_compiled(&mut self, compiled: bool) {
+        self.compiled = compiled;
+    }
+
+    // Whether to count how often each instruction runs, from the next load onwards
+    pub fn set_profiled(&mut self, profiled: bool) {
+        self.profiled = profiled;
+    }
+
+    // The counts so far, if profiling
+    pub fn profile(&self) -> Option<&Profile> {
+        self.profile.as_ref()
+    }
+
+    // Records a trace of everything from the next load onwards
+    pub fn trace_to(&mut self, out: Box<dyn Write>) {
+        self.tracer = Some(Tracer::new(out));
+    }
+
+    // Stops tracing, reporting any error from writing the trace
+    pub fn finish_trace(&mut self) -> io::Result<()> {
+        match self.tracer.take() {
+            Some(tracer) => tracer.finish(),
+            None => Ok(())
+        }
+    }
+
+    // Loads a program, ready to run from the start. Registers are left alone, apart from adding any
+    // more the program uses, which start at 0.
+    pub fn load(&mut self, instructions: Vec<Instruction>) {
+        let needed = registers_used(&instructions);
+        if self.regs.len() < needed {
+            self.regs.resize(needed, 0);
+        }
+        self.unoptimised_instructions = instructions;
+        self.reoptimise();
+        self.pc = 0;
+        self.input_exhausted = false;
+
+        self.profile = if self.profiled {
+            Some(Profile::new(self.instructions.len()))
+        } else {
+            None
+        };
+
+        self.compiled_instructions = if self.compiled && self.tracer.is_none() && self.profile.is_none() {
+            Some(compile(&self.instructions, &self.unoptimised_instructions))
+        } else {
+            None
+        };
+
+        if let Some(ref mut tracer) = self.tracer {
+            tracer.record(&TraceEvent::Start(TraceStart {
+                registers: self.regs.clone(),
+                program: self.unoptimised_instructions.iter().map(|i| i.to_string()).collect(),
+                optimised: self.optimised
+            }));
+        }
+    }
+
+    fn reoptimise(&mut self) {
+        self.instructions = if self.optimised {
+            optimise(&self.unoptimised_instructions)
+        } else {
+            self.unoptimised_instructions.clone()
+        };
+    }
+
+    pub fn pc(&self) -> i32 {
+        self.pc
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.input_exhausted || self.pc < 0 || self.pc >= self.instructions.len() as i32
+    }
+
+    // Whether the program stopped on an `in` because there was no more input
+    pub fn is_waiting_for_input(&self) -> bool {
+        self.input_exhausted
+    }
+
+    // The program as it is being run, with optimised blocks followed by Nops
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    // The program as written, plus any toggles made so far
+    pub fn unoptimised_instructions(&self) -> &[Instruction] {
+        &self.unoptimised_instructions
+    }
+
+    fn value(&self, reg_or_val: Operand) -> i64 {
+        match reg_or_val {
+            Register(reg_idx) => self.regs[reg_idx],
+            Literal(value) => value
+        }
+    }
+
+    fn set_value(&mut self, reg: Operand, reg_or_val: Operand) {
+        if let Register(reg_idx) = reg {
+            let value = self.value(reg_or_val);
+            self.regs[reg_idx] = value;
+        } else {
+            panic!("Trying to set a value to a literal: {:?} = {:?}", reg, reg_or_val);
+        }
+    }
+
+    // Runs the program until it finishes (returning None), the execution environment stops it, or
+    // an instruction overflows
+    pub fn process(&mut self, instructions: Vec<Instruction>) -> Result<Option<T>, Overflow> {
+        self.load(instructions);
+
+        if let Some(step) = self.run_compiled() {
+            return match step {
+                Step::Overflowed(overflow) => Err(overflow),
+                Step::Terminated(reason) => Ok(Some(reason)),
+                Step::Running | Step::Finished => unreachable!()
+            };
+        }
+
+        loop {
+            match self.step() {
+                Step::Running => {},
+                Step::Finished => return Ok(None),
+                Step::Terminated(reason) => return Ok(Some(reason)),
+                Step::Overflowed(overflow) => return Err(overflow)
+            }
+        }
+    }
+
+    // Runs the compiled program until it finishes, or reaches a tgl which would change it (returning
+    // None, for the interpreter to carry on from there), or else stops with the environment's reason
+    // or an overflow
+    fn run_compiled(&mut self) -> Option<Step<T>> {
+        let compiled = self.compiled_instructions.take()?;
+
+        while !self.is_finished() {
+            match compiled[self.pc as usize](&mut self.regs) {
+                Control::Jump(delta) => self.pc = jump(self.pc, delta),
+                Control::Output(val) => {
+                    self.exec_env.handle_output(val);
+                    self.pc += 1;
+                },
+                Control::Input(reg_idx) => {
+                    match self.exec_env.handle_input() {
+                        Some(val) => {
+                            self.regs[reg_idx] = val;
+                            self.pc += 1;
+                        },
+                        None => self.input_exhausted = true
+                    }
+                },
+                Control::Toggle => return None,
+                Control::Overflow => return Some(self.overflowed())
+            }
+
+            if let Some(reason) = self.exec_env.should_terminate(&self.regs, self.pc as usize) {
+                return Some(Step::Terminated(reason));
+            }
+        }
+        None
+    }
+
+    // The instruction to run at the program counter. A jump by a register can land in the middle of
+    // an optimised loop, so the original instructions are run from there until the loop comes back
+    // round to its start.
+    fn current_instruction(&self) -> Instruction {
+        match self.instructions[self.pc as usize] {
+            Nop => self.unoptimised_instructions[self.pc as usize],
+            instr => instr
+        }
+    }
+
+    fn overflowed(&self) -> Step<T> {
+        Step::Overflowed(Overflow {
+            pc: self.pc,
+            instruction: self.current_instruction().to_string()
+        })
+    }
+
+    // Executes the instruction at the program counter
+    pub fn step(&mut self) -> Step<T> {
+        if self.is_finished() {
+            return Step::Finished;
+        }
+
+        let pc = self.pc;
+        // Only copied when there's a tracer to record which registers changed
+        let regs_before = self.tracer.as_ref().map(|_| self.regs.clone());
+        let mut toggled = None;
+        let mut output = None;
+        let mut input = None;
+
+        let instr = self.current_instruction();
+        if let Some(ref mut profile) = self.profile {
+            profile.record(pc as usize, instr, &self.regs);
+        }
+        match instr {
+            Copy { source, target } => {
+                // Toggling can produce nonsense like `cpy 1 2`, which is skipped
+                if let Register(_) = target {
+                    self.set_value(target, source);
+                }
+            },
+            Inc { reg } => {
+                if let Register(reg_idx) = reg {
+                    match self.regs[reg_idx].checked_add(1) {
+                        Some(value) => self.regs[reg_idx] = value,
+                        None => return self.overflowed()
+                    }
+                }
+            },
+            Dec { reg } => {
+                if let Register(reg_idx) = reg {
+                    match self.regs[reg_idx].checked_sub(1) {
+                        Some(value) => self.regs[reg_idx] = value,
+                        None => return self.overflowed()
+                    }
+                }
+            },
+            JumpNotZero { check, delta } => {
+                let value = self.value(check);
+                if value != 0 {
+                    // Step back one, to cancel out moving on to the next instruction below
+                    self.pc = jump(self.pc, self.value(delta)) - 1;
+                }
+            },
+            Toggle { reg } => {
+                let idx = (self.pc as i64).saturating_add(self.value(reg));
+                if idx >= 0 && (idx as usize) < self.instructions.len() {
+                    // Toggle the un-optimised instruction, even if it is part of an optimised block
+                    let orig = self.unoptimised_instructions[idx as usize];
+                    let new = orig.toggle();
+                    self.unoptimised_instructions[idx as usize] = new;
+                    toggled = Some((idx as i32, orig, new));
+
+                    // Re-optimise the instructions, which breaks the block up if it no longer
+                    // makes a loop the optimiser recognises, or works out what it does now
+                    self.reoptimise();
+                }
+            },
+            Out { operand } => {
+                let val = self.value(operand);
+                self.exec_env.handle_output(val);
+                output = Some(val);
+            },
+            In { reg: Register(reg_idx) } => {
+                match self.exec_env.handle_input() {
+                    Some(val) => {
+                        self.regs[reg_idx] = val;
+                        input = Some(val);
+                    },
+                    None => {
+                        // Stay on the `in`, so the program can be picked up again with more input
+                        self.input_exhausted = true;
+                        self.pc -= 1;
+                    }
+                }
+            },
+            // The parser won't accept `in 3`, and toggling never produces an `in`
+            In { reg: Literal(_) } => {},
+            Loop{ counter, effects, len } => {
+                if run_loop(counter, &effects, &mut self.regs).is_none() {
+                    return self.overflowed();
+                }
+
+                // Skip over the following Nops
+                self.pc += len as i32 - 1;
+            },
+            Nop => unreachable!()
+        }
+
+        self.pc += 1;
+
+        if let (Some(tracer), Some(regs_before)) = (self.tracer.as_mut(), regs_before) {
+            let mut changes = BTreeMap::new();
+            for reg_idx in 0..self.regs.len() {
+                if regs_before[reg_idx] != self.regs[reg_idx] {
+                    changes.insert(Register(reg_idx).to_string(), (regs_before[reg_idx], self.regs[reg_idx]));
+                }
+            }
+            let step = tracer.next_step();
+            tracer.record(&TraceEvent::Step(TraceStep {
+                step: step,
+                pc: pc,
+                instruction: instr.to_string(),
+                changes: changes,
+                toggles: toggled.into_iter()
+                    .map(|(idx, from, to)| Toggled { pc: idx, from: from.to_string(), to: to.to_string() })
+                    .collect(),
+                output: output,
+                input: input
+            }));
+        }
+
+        if let Some(reason) = self.exec_env.should_terminate(&self.regs, self.pc as usize) {
+            return Step::Terminated(reason);
+        }
+
+        if self.is_finished() {
+            Step::Finished
+        } else {
+            Step::Running
+        }
+    }
+}
+
+#[cfg(test)]
+use parser::parse;
+
+#[test]
+fn optimised_multiply_produces_same_result_as_normal() {
+    let file = r"cpy a b
+dec b
+cpy a d
+cpy 0 a
+cpy b c
+inc a
+dec c
+jnz c -2
+dec d
+jnz d -5
+dec b
+cpy b c
+cpy c d
+dec d
+inc c
+jnz d -2
+tgl c
+cpy -16 c
+jnz 1 c
+cpy 96 c
+jnz 79 d
+inc a
+inc d
+jnz d -2
+inc c
+jnz c -5";
+    let normal = parse(file).unwrap();
+    let optimised = optimise(&normal.clone());
+
+    let mut cpu = Cpu::new(&[7, 0, 0, 0]);
+    cpu.set_optimised(false);
+    cpu.process(normal).unwrap();
+
+    let mut cpu2 = Cpu::new(&[7, 0, 0, 0]);
+    cpu2.process(optimised).unwrap();
+
+    assert_eq!(cpu.regs, cpu2.regs);
+}
+
+#[test]
+fn toggled_copy_to_a_literal_is_skipped() {
+    let file = r"cpy 1 a
+tgl a
+jnz 5 7
+inc a";
+    let mut cpu = Cpu::new(&[0, 0, 0, 0]);
+    cpu.process(parse(file).unwrap()).unwrap();
+    assert_eq!(cpu.regs, [2, 0, 0, 0]);
+}
+
+#[test]
+fn jumping_into_the_middle_of_an_optimised_loop_runs_the_original_instructions() {
+    // Found by the fuzzer: a jnz by b lands on the `dec b` swallowed by the loop at 2
+    let file = r"jnz a b
+cpy a b
+inc a
+dec b
+jnz b -2";
+    let mut cpu = Cpu::new(&[1, 3, 0, 0]);
+    cpu.process(parse(file).unwrap()).unwrap();
+    assert_eq!(cpu.regs, [3, 0, 0, 0]);
+}
+
+#[test]
+fn compiled_program_hands_over_to_the_interpreter_when_toggled() {
+    let file = r"cpy 2 a
+tgl a
+tgl a
+tgl a
+cpy 1 a
+dec a
+dec a";
+    let mut cpu = Cpu::new(&[0, 0, 0, 0]);
+    cpu.set_compiled(true);
+    cpu.process(parse(file).unwrap()).unwrap();
+    assert_eq!(cpu.regs[0], 3);
+}
+
+#[test]
+fn overflow_is_reported_at_the_instruction_which_caused_it() {
+    let file = r"cpy 9223372036854775806 a
+inc a
+inc a";
+    for &compiled in &[false, true] {
+        let mut cpu = Cpu::new(&[0, 0, 0, 0]);
+        cpu.set_compiled(compiled);
+        let overflow = Overflow { pc: 2, instruction: "inc a".to_string() };
+        assert_eq!(cpu.process(parse(file).unwrap()), Err(overflow));
+        assert_eq!((cpu.pc(), cpu.regs[0]), (2, i64::MAX));
+    }
+}
+
+#[test]
+fn overflowing_optimised_loop_leaves_the_registers_alone() {
+    // 2^62 * 2 doesn't fit
+    let file = r"cpy 4611686018427387904 b
+cpy 2 d
+cpy b c
+inc a
+dec c
+jnz c -2
+dec d
+jnz d -5";
+    let mut cpu = Cpu::new(&[0, 0, 0, 0]);
+    let overflow = cpu.process(parse(file).unwrap()).unwrap_err();
+    assert_eq!(overflow.to_string(), "Overflow at 2: a += d * b; c = 0; d = 0");
+    assert_eq!(cpu.regs, [0, 4611686018427387904, 0, 2]);
+}
+
+#[test]
+fn registers_beyond_d_start_at_0() {
+    let file = r"cpy 5 e
+inc f
+inc a";
+    let mut cpu = Cpu::new(&[1]);
+    cpu.process(::parser::parse_with_registers(file, 6).unwrap()).unwrap();
+    assert_eq!(cpu.regs, [2, 0, 0, 0, 5, 1]);
+    assert_eq!(format_registers(&cpu.regs), "a=2 b=0 c=0 d=0 e=5 f=1");
+}
+
+// d * b is added to a by the loop at 4, unless the tgl at 3 changes part of it first
+#[cfg(test)]
+static TOGGLED_MULTIPLY: &'static str = r"cpy 3 b
+cpy 4 d
+cpy 0 c
+tgl c
+cpy b c
+inc a
+dec c
+jnz c -2
+dec d
+jnz d -5";
+
+// Runs TOGGLED_MULTIPLY with the tgl hitting the given line, checking the optimised and compiled
+// runs agree with the raw one. Returns the final registers and the program as it ends up optimised.
+#[cfg(test)]
+fn run_toggled_multiply(line: i64) -> (Vec<i64>, Vec<Instruction>) {
+    let mut instructions = parse(TOGGLED_MULTIPLY).unwrap();
+    instructions[2] = Copy{ source: Literal(line - 3), target: Register(2) };
+
+    let mut raw = Cpu::new(&[0, 0, 0, 0]);
+    raw.set_optimised(false);
+    raw.process(instructions.clone()).unwrap();
+
+    for &compiled in &[false, true] {
+        let mut cpu = Cpu::new(&[0, 0, 0, 0]);
+        cpu.set_compiled(compiled);
+        cpu.process(instructions.clone()).unwrap();
+        assert_eq!(cpu.regs, raw.regs, "Compiled: {}", compiled);
+    }
+
+    let mut cpu = Cpu::new(&[0, 0, 0, 0]);
+    cpu.process(instructions).unwrap();
+    (cpu.regs.clone(), cpu.instructions().to_vec())
+}
+
+#[test]
+fn toggle_inside_a_multiply_loop_is_optimised_again() {
+    // inc a -> dec a, which still multiplies
+    let (regs, instructions) = run_toggled_multiply(5);
+    assert_eq!(regs, [-12, 3, 0, 0]);
+    assert_eq!(instructions[4].to_string(), "a += -1 * d * b; c = 0; d = 0");
+    assert!(instructions[5..10].iter().all(|&instr| instr == Nop));
+}
+
+#[test]
+fn toggle_breaking_up_a_multiply_loop_deoptimises_it() {
+    // jnz c -2 -> cpy c -2, so there's no inner loop, and the outer one sets c to b - 1 each time
+    let (regs, instructions) = run_toggled_multiply(7);
+    assert_eq!(regs, [4, 3, 2, 0]);
+    assert!(!instructions.contains(&Nop), "Got {:?}", instructions);
+    assert_eq!(instructions[7].to_string(), "cpy c -2");
+}
+
+#[test]
+fn toggle_of_the_loop_counter_inside_a_multiply_loop() {
+    // dec c -> inc c, so the inner loop never ends and there's nothing left to optimise
+    let mut instructions = parse(TOGGLED_MULTIPLY).unwrap();
+    instructions[2] = Copy{ source: Literal(3), target: Register(2) };
+    let mut cpu = Cpu::with_env(&[0, 0, 0, 0], Box::new(::devices::StepBudget::new(100, ())));
+    assert_eq!(cpu.process(instructions).unwrap(), Some(()));
+    assert!(!cpu.instructions().contains(&Nop), "Got {:?}", cpu.instructions());
+}
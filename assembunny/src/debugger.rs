@@ -0,0 +1,351 @@
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::io::{self, BufRead, Write};
+use std::rc::Rc;
+
+use ::cpu::{Cpu, ExecutionEnvironment, Step, format_registers};
+use ::parser::{Instruction, MAX_REGISTERS};
+use ::parser::Instruction::*;
+use ::parser::Operand;
+use ::parser::Operand::*;
+
+static HELP: &'static str = "Commands:
+  break <pc>     (b)  stop when the program counter reaches pc
+  delete <pc>    (d)  remove a breakpoint
+  watch <reg>    (w)  stop whenever the register changes
+  unwatch <reg>       stop watching a register
+  step [n]       (s)  execute n instructions (default 1)
+  continue       (c)  run until a breakpoint, a watched register changes or the program ends
+  regs           (r)  show the registers
+  list           (l)  show the instructions around the program counter
+  quit           (q)";
+
+// Keeps hold of `out` values so the debugger can show them as they happen
+struct RecordOutput {
+    outputs: Rc<RefCell<Vec<i64>>>
+}
+
+impl ExecutionEnvironment<()> for RecordOutput {
+    fn handle_output(&mut self, val: i64) {
+        self.outputs.borrow_mut().push(val);
+    }
+
+    fn should_terminate(&mut self, _: &[i64], _: usize) -> Option<()> {
+        None
+    }
+}
+
+pub struct Debugger<W: Write> {
+    cpu: Cpu<()>,
+    breakpoints: BTreeSet<i32>,
+    watches: BTreeSet<usize>,
+    outputs: Rc<RefCell<Vec<i64>>>,
+    outputs_shown: usize,
+    out: W
+}
+
+impl<W: Write> Debugger<W> {
+    pub fn new(instructions: Vec<Instruction>, regs: &[i64], out: W) -> Debugger<W> {
+        let outputs = Rc::new(RefCell::new(vec![]));
+        let mut cpu = Cpu::with_env(regs, Box::new(RecordOutput { outputs: outputs.clone() }));
+        cpu.load(instructions);
+
+        Debugger {
+            cpu: cpu,
+            breakpoints: BTreeSet::new(),
+            watches: BTreeSet::new(),
+            outputs: outputs,
+            outputs_shown: 0,
+            out: out
+        }
+    }
+
+    // Reads commands until `quit` or the end of the input. Interactive sessions get a prompt, while
+    // scripted ones have each command echoed so the output makes sense on its own.
+    pub fn run<R: BufRead>(&mut self, commands: R, interactive: bool) -> io::Result<()> {
+        self.show_position()?;
+
+        if interactive {
+            self.prompt()?;
+        }
+        for line in commands.lines() {
+            let line = line?;
+            if !interactive {
+                writeln!(self.out, "(bunny) {}", line)?;
+            }
+            if !self.execute(line.trim())? {
+                break;
+            }
+            if interactive {
+                self.prompt()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn prompt(&mut self) -> io::Result<()> {
+        write!(self.out, "(bunny) ")?;
+        self.out.flush()
+    }
+
+    // Runs a single command, returning false if it was asking to quit
+    pub fn execute(&mut self, command: &str) -> io::Result<bool> {
+        let mut words = command.split_whitespace();
+        let name = match words.next() {
+            Some(name) => name,
+            None => return Ok(true)
+        };
+        let arg = words.next();
+
+        match (name, arg) {
+            ("break", Some(pc)) | ("b", Some(pc)) => {
+                match pc.parse::<i32>() {
+                    Ok(pc) => self.add_breakpoint(pc)?,
+                    Err(_) => writeln!(self.out, "Not a program counter: {}", pc)?
+                }
+            },
+            ("delete", Some(pc)) | ("d", Some(pc)) => {
+                match pc.parse::<i32>() {
+                    Ok(pc) if self.breakpoints.remove(&pc) => writeln!(self.out, "Removed breakpoint at {}", pc)?,
+                    _ => writeln!(self.out, "No breakpoint at {}", pc)?
+                }
+            },
+            ("watch", Some(reg)) | ("w", Some(reg)) => {
+                match self.register(reg) {
+                    Some(reg_idx) => {
+                        self.watches.insert(reg_idx);
+                        writeln!(self.out, "Watching {} = {}", reg, self.cpu.regs[reg_idx])?;
+                    },
+                    None => writeln!(self.out, "Not a register: {}", reg)?
+                }
+            },
+            ("unwatch", Some(reg)) => {
+                match self.register(reg) {
+                    Some(reg_idx) if self.watches.remove(&reg_idx) => writeln!(self.out, "Stopped watching {}", reg)?,
+                    _ => writeln!(self.out, "Not watching {}", reg)?
+                }
+            },
+            ("step", count) | ("s", count) => {
+                match count.map_or(Ok(1), |c| c.parse::<usize>()) {
+                    Ok(count) => {
+                        for _ in 0..count {
+                            if self.step()? {
+                                break;
+                            }
+                        }
+                        self.show_position()?;
+                    },
+                    Err(_) => writeln!(self.out, "Not a number of steps: {}", count.unwrap())?
+                }
+            },
+            ("continue", None) | ("c", None) => {
+                // Always make some progress, even when sitting on a breakpoint
+                let mut stopped = self.step()?;
+                while !stopped {
+                    if self.breakpoints.contains(&self.cpu.pc()) {
+                        writeln!(self.out, "Breakpoint at {}", self.cpu.pc())?;
+                        break;
+                    }
+                    stopped = self.step()?;
+                }
+                self.show_position()?;
+            },
+            ("regs", None) | ("r", None) => self.show_registers()?,
+            ("list", None) | ("l", None) => self.list()?,
+            ("help", None) | ("h", None) => writeln!(self.out, "{}", HELP)?,
+            ("quit", None) | ("q", None) => return Ok(false),
+            _ => writeln!(self.out, "Didn't understand '{}', try 'help'", command)?
+        }
+
+        Ok(true)
+    }
+
+    // The index of the named register, if the CPU has it. A program might not use any registers at
+    // all, and then there's nothing to name.
+    fn register(&self, name: &str) -> Option<usize> {
+        let num_registers = self.cpu.regs.len();
+        if !(1..=MAX_REGISTERS).contains(&num_registers) {
+            return None;
+        }
+        match Operand::parse_with_registers(name, num_registers) {
+            Ok(Register(reg_idx)) => Some(reg_idx),
+            _ => None
+        }
+    }
+
+    fn add_breakpoint(&mut self, pc: i32) -> io::Result<()> {
+        self.breakpoints.insert(pc);
+
+        // Instructions swallowed by an optimisation are normally skipped over, so are unlikely to be stopped at
+        let instructions = self.cpu.instructions();
+        if pc >= 0 && (pc as usize) < instructions.len() && instructions[pc as usize] == Nop {
+            let block_start = (0..pc).rev()
+                .find(|&idx| instructions[idx as usize] != Nop)
+                .unwrap_or(0);
+            writeln!(self.out, "Breakpoint set at {}, but it is inside the optimised block at {} so won't be hit \
+                                unless a jump lands inside it or a tgl breaks the block up", pc, block_start)
+        } else {
+            writeln!(self.out, "Breakpoint set at {}", pc)
+        }
+    }
+
+    // Executes one instruction, reporting anything interesting along the way. Returns true if
+    // execution should stop: the program has finished or overflowed, or a watched register has changed.
+    fn step(&mut self) -> io::Result<bool> {
+        if self.cpu.is_finished() {
+            return Ok(true);
+        }
+
+        let pc = self.cpu.pc();
+        let instr = self.cpu.instructions()[pc as usize];
+        let regs_before = self.cpu.regs.clone();
+        let unoptimised_before = if let Toggle{..} = instr {
+            Some(self.cpu.unoptimised_instructions().to_vec())
+        } else {
+            None
+        };
+
+        let finished = match self.cpu.step() {
+            Step::Finished => true,
+            Step::Overflowed(overflow) => {
+                writeln!(self.out, "{}", overflow)?;
+                true
+            },
+            Step::Running | Step::Terminated(()) => false
+        };
+
+        if let Some(before) = unoptimised_before {
+            let after = self.cpu.unoptimised_instructions();
+            for (idx, (old, new)) in before.iter().zip(after.iter()).enumerate() {
+                if old != new {
+                    writeln!(self.out, "tgl at {} changed {}: '{}' -> '{}'", pc, idx, old, new)?;
+                }
+            }
+        }
+
+        let outputs = self.outputs.borrow();
+        for val in &outputs[self.outputs_shown..] {
+            writeln!(self.out, "out: {}", val)?;
+        }
+        self.outputs_shown = outputs.len();
+
+        let mut watch_hit = false;
+        for &reg_idx in &self.watches {
+            if regs_before[reg_idx] != self.cpu.regs[reg_idx] {
+                writeln!(self.out, "Watch {}: {} -> {} (at {}: {})",
+                         Register(reg_idx), regs_before[reg_idx], self.cpu.regs[reg_idx], pc, instr)?;
+                watch_hit = true;
+            }
+        }
+
+        Ok(finished || watch_hit)
+    }
+
+    fn show_position(&mut self) -> io::Result<()> {
+        if self.cpu.is_waiting_for_input() {
+            // The debugger has no input to give, so an `in` is as far as the program can get
+            let pc = self.cpu.pc();
+            let line = self.describe(pc as usize);
+            writeln!(self.out, "Stopped waiting for input at {}: {}", pc, line)?;
+        } else if self.cpu.is_finished() {
+            writeln!(self.out, "Program finished")?;
+        } else {
+            let pc = self.cpu.pc();
+            let line = self.describe(pc as usize);
+            writeln!(self.out, "{}: {}", pc, line)?;
+        }
+        self.show_registers()
+    }
+
+    fn show_registers(&mut self) -> io::Result<()> {
+        writeln!(self.out, "{}", format_registers(&self.cpu.regs))
+    }
+
+    // The instruction as written, plus what actually runs if it has been optimised
+    fn describe(&self, idx: usize) -> String {
+        let unoptimised = self.cpu.unoptimised_instructions()[idx];
+        let optimised = self.cpu.instructions()[idx];
+        if unoptimised == optimised {
+            unoptimised.to_string()
+        } else {
+            format!("{:<10} [runs as: {}]", unoptimised.to_string(), optimised)
+        }
+    }
+
+    fn list(&mut self) -> io::Result<()> {
+        let len = self.cpu.instructions().len() as i32;
+        let pc = self.cpu.pc();
+        let from = ::std::cmp::max(0, ::std::cmp::min(pc, len) - 5);
+        let to = ::std::cmp::min(len, pc + 6);
+
+        for idx in from..to {
+            let marker = if idx == pc { '>' } else if self.breakpoints.contains(&idx) { '*' } else { ' ' };
+            let line = self.describe(idx as usize);
+            writeln!(self.out, "{} {:>3}: {}", marker, idx, line)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+fn debug_script(program: &str, regs: &[i64], script: &str) -> String {
+    let mut output = vec![];
+    {
+        let mut debugger = Debugger::new(::parser::parse(program).unwrap(), regs, &mut output);
+        debugger.run(script.as_bytes(), false).unwrap();
+    }
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn continue_stops_at_breakpoint() {
+    let program = r"cpy 3 a
+dec a
+jnz a -1
+inc b";
+    let output = debug_script(program, &[0, 0, 0, 0], "b 3\nc");
+    assert!(output.ends_with("Breakpoint at 3\n3: inc b\na=0 b=0 c=0 d=0\n"), "Got:\n{}", output);
+}
+
+#[test]
+fn watch_stops_when_register_changes() {
+    let program = r"cpy 3 a
+inc b
+inc c";
+    let output = debug_script(program, &[0, 0, 0, 0], "w c\nc");
+    assert!(output.contains("Watch c: 0 -> 1 (at 2: inc c)"), "Got:\n{}", output);
+}
+
+#[test]
+fn optimised_instruction_is_shown_next_to_original() {
+    let program = r"dec d
+inc c
+jnz d -2";
+    let output = debug_script(program, &[0, 0, 0, 2], "");
+    assert!(output.starts_with("0: dec d      [runs as: c += d; d = 0]"), "Got:\n{}", output);
+}
+
+#[test]
+fn toggles_are_reported() {
+    let output = debug_script("tgl 1\ninc a", &[0, 0, 0, 0], "s");
+    assert!(output.contains("tgl at 0 changed 1: 'inc a' -> 'dec a'"), "Got:\n{}", output);
+}
+
+#[test]
+fn overflow_stops_the_program_where_it_happened() {
+    let output = debug_script("cpy 9223372036854775807 a\ninc a\ninc b", &[0, 0, 0, 0], "c");
+    assert!(output.ends_with("Overflow at 1: inc a\n1: inc a\na=9223372036854775807 b=0 c=0 d=0\n"), "Got:\n{}", output);
+}
+
+#[test]
+fn watching_without_any_registers_is_refused() {
+    let output = debug_script("jnz 1 1", &[], "w a\nunwatch a");
+    assert!(output.ends_with("(bunny) w a\nNot a register: a\n(bunny) unwatch a\nNot watching a\n"), "Got:\n{}", output);
+}
+
+#[test]
+fn running_out_of_input_is_shown_as_waiting() {
+    let output = debug_script("inc a\nin b\ninc c", &[0, 0, 0, 0], "c");
+    assert!(output.ends_with("Stopped waiting for input at 1: in b\na=1 b=0 c=0 d=0\n"), "Got:\n{}", output);
+}
@@ -0,0 +1,220 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::io::{self, BufRead, StdinLock, Stdout, Write};
+use std::rc::Rc;
+
+use ::cpu::ExecutionEnvironment;
+
+// Ready-made execution environments, for programs which do I/O with `in` and `out`. None of them
+// care why a program is stopped, so they can be used with any `Cpu<T>`, and chained together.
+
+// Feeds `in` from a fixed list of values, and collects everything output. The outputs are shared,
+// so they can still be read once the environment has been handed over to a CPU.
+pub struct BufferedIo {
+    input: VecDeque<i64>,
+    outputs: Rc<RefCell<Vec<i64>>>
+}
+
+impl BufferedIo {
+    pub fn new(input: Vec<i64>) -> BufferedIo {
+        BufferedIo {
+            input: input.into_iter().collect(),
+            outputs: Rc::new(RefCell::new(vec![]))
+        }
+    }
+
+    pub fn outputs(&self) -> Rc<RefCell<Vec<i64>>> {
+        self.outputs.clone()
+    }
+}
+
+impl<T> ExecutionEnvironment<T> for BufferedIo {
+    fn handle_output(&mut self, val: i64) {
+        self.outputs.borrow_mut().push(val);
+    }
+
+    fn handle_input(&mut self) -> Option<i64> {
+        self.input.pop_front()
+    }
+
+    fn should_terminate(&mut self, _: &[i64], _: usize) -> Option<T> {
+        None
+    }
+}
+
+// Reads `in` values as whitespace separated numbers, a line at a time so it works interactively,
+// and writes each `out` value on its own line. The end of the reader, a read error, or anything
+// which isn't a number ends the input. Write errors are ignored, as the CPU has no way to report
+// them, so add a StepBudget if the program might otherwise output forever.
+pub struct StreamIo<R: BufRead, W: Write> {
+    reader: R,
+    writer: W,
+    pending: VecDeque<i64>,
+    ended: bool
+}
+
+impl<R: BufRead, W: Write> StreamIo<R, W> {
+    pub fn new(reader: R, writer: W) -> StreamIo<R, W> {
+        StreamIo {
+            reader: reader,
+            writer: writer,
+            pending: VecDeque::new(),
+            ended: false
+        }
+    }
+
+    fn read_line(&mut self) {
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(0) | Err(_) => self.ended = true,
+            Ok(_) => {
+                for word in line.split_whitespace() {
+                    match word.parse::<i64>() {
+                        Ok(val) => self.pending.push_back(val),
+                        Err(_) => {
+                            self.ended = true;
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl StreamIo<StdinLock<'static>, Stdout> {
+    pub fn stdio() -> StreamIo<StdinLock<'static>, Stdout> {
+        StreamIo::new(io::stdin().lock(), io::stdout())
+    }
+}
+
+impl<T, R: BufRead, W: Write> ExecutionEnvironment<T> for StreamIo<R, W> {
+    fn handle_output(&mut self, val: i64) {
+        let _ = writeln!(self.writer, "{}", val).and_then(|_| self.writer.flush());
+    }
+
+    fn handle_input(&mut self) -> Option<i64> {
+        while self.pending.is_empty() && !self.ended {
+            self.read_line();
+        }
+        self.pending.pop_front()
+    }
+
+    fn should_terminate(&mut self, _: &[i64], _: usize) -> Option<T> {
+        None
+    }
+}
+
+// Stops the program with the given reason once it has run more than max_steps instructions
+pub struct StepBudget<T: Clone> {
+    max_steps: usize,
+    steps: usize,
+    reason: T
+}
+
+impl<T: Clone> StepBudget<T> {
+    pub fn new(max_steps: usize, reason: T) -> StepBudget<T> {
+        StepBudget {
+            max_steps: max_steps,
+            steps: 0,
+            reason: reason
+        }
+    }
+}
+
+impl<T: Clone> ExecutionEnvironment<T> for StepBudget<T> {
+    fn handle_output(&mut self, _: i64) {}
+
+    fn should_terminate(&mut self, _: &[i64], _: usize) -> Option<T> {
+        self.steps += 1;
+        if self.steps > self.max_steps {
+            Some(self.reason.clone())
+        } else {
+            None
+        }
+    }
+}
+
+// Combines several environments: output goes to all of them, input comes from the first one which
+// has some, and the program stops as soon as any of them asks it to. Every environment is asked
+// about stopping each step, so things like step counts stay right.
+pub struct Chain<T> {
+    envs: Vec<Box<dyn ExecutionEnvironment<T>>>
+}
+
+impl<T> Chain<T> {
+    pub fn new(envs: Vec<Box<dyn ExecutionEnvironment<T>>>) -> Chain<T> {
+        Chain {
+            envs: envs
+        }
+    }
+}
+
+impl<T> ExecutionEnvironment<T> for Chain<T> {
+    fn handle_output(&mut self, val: i64) {
+        for env in &mut self.envs {
+            env.handle_output(val);
+        }
+    }
+
+    fn handle_input(&mut self) -> Option<i64> {
+        self.envs.iter_mut()
+            .filter_map(|env| env.handle_input())
+            .next()
+    }
+
+    fn should_terminate(&mut self, registers: &[i64], program_counter: usize) -> Option<T> {
+        let mut reason = None;
+        for env in &mut self.envs {
+            let env_reason = env.should_terminate(registers, program_counter);
+            if reason.is_none() {
+                reason = env_reason;
+            }
+        }
+        reason
+    }
+}
+
+#[cfg(test)]
+use ::cpu::Cpu;
+#[cfg(test)]
+use ::parser::parse;
+
+#[cfg(test)]
+static ECHO: &'static str = r"in a
+out a
+jnz 1 -2";
+
+#[test]
+fn buffered_io_echoes_until_the_input_runs_out() {
+    let io = BufferedIo::new(vec![3, 1, 4]);
+    let outputs = io.outputs();
+
+    let mut cpu: Cpu<()> = Cpu::with_env(&[0, 0, 0, 0], Box::new(io));
+    assert_eq!(cpu.process(parse(ECHO).unwrap()), Ok(None));
+    assert_eq!(*outputs.borrow(), vec![3, 1, 4]);
+    assert_eq!(cpu.pc(), 0);
+}
+
+#[test]
+fn stream_io_reads_numbers_until_something_else() {
+    let mut io = StreamIo::new(&b"5 -2\n\n7 x 8\n"[..], vec![]);
+    let mut inputs = vec![];
+    while let Some(val) = ExecutionEnvironment::<()>::handle_input(&mut io) {
+        inputs.push(val);
+        ExecutionEnvironment::<()>::handle_output(&mut io, val * 2);
+    }
+    assert_eq!(inputs, vec![5, -2, 7]);
+    assert_eq!(String::from_utf8(io.writer).unwrap(), "10\n-4\n14\n");
+}
+
+#[test]
+fn chained_step_budget_stops_endless_output() {
+    let io = BufferedIo::new(vec![]);
+    let outputs = io.outputs();
+    let chain = Chain::new(vec![Box::new(io), Box::new(StepBudget::new(6, "out of steps"))]);
+
+    let mut cpu = Cpu::with_env(&[0, 0, 0, 0], Box::new(chain));
+    assert_eq!(cpu.process(parse("out 1\njnz 1 -1").unwrap()), Ok(Some("out of steps")));
+    assert_eq!(*outputs.borrow(), vec![1, 1, 1, 1]);
+}
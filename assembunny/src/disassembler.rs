@@ -0,0 +1,236 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, Write};
+
+use ::optimiser::optimise;
+use ::parser::Instruction;
+use ::parser::Instruction::*;
+use ::parser::Operand::*;
+
+// Where a `jnz` with a literal offset lands, unless it can never jump
+fn jump_target(pc: usize, instr: Instruction) -> Option<i64> {
+    match instr {
+        JumpNotZero{ check: Literal(0), .. } => None,
+        JumpNotZero{ delta: Literal(delta), .. } => Some((pc as i64).saturating_add(delta)),
+        _ => None
+    }
+}
+
+fn in_program(target: i64, instructions: &[Instruction]) -> bool {
+    target >= 0 && (target as usize) < instructions.len()
+}
+
+// Names every jump target inside the program L1, L2... in order
+fn labels(instructions: &[Instruction]) -> BTreeMap<usize, String> {
+    let targets:BTreeSet<usize> = instructions.iter().enumerate()
+        .filter_map(|(pc, &instr)| jump_target(pc, instr))
+        .filter(|&target| in_program(target, instructions))
+        .map(|target| target as usize)
+        .collect();
+
+    targets.into_iter()
+        .enumerate()
+        .map(|(i, target)| (target, format!("L{}", i + 1)))
+        .collect()
+}
+
+// The loops the optimiser replaces, as (first line, line after the loop, what the loop does)
+fn optimised_regions(instructions: &[Instruction]) -> Vec<(usize, usize, String)> {
+    optimise(instructions).iter().enumerate()
+        .filter_map(|(pc, instr)| match *instr {
+            Loop{ len, .. } => Some((pc, pc + len, instr.to_string())),
+            _ => None
+        })
+        .collect()
+}
+
+// The instruction as written, but jumping to a label rather than an offset where it can
+fn with_label(pc: usize, instr: Instruction, labels: &BTreeMap<usize, String>) -> String {
+    let label = jump_target(pc, instr)
+        .filter(|&target| target >= 0)
+        .and_then(|target| labels.get(&(target as usize)));
+    match (instr, label) {
+        (JumpNotZero{ check, .. }, Some(label)) => format!("jnz {} {}", check, label),
+        _ => instr.to_string()
+    }
+}
+
+// Anything worth pointing out which can't be seen from the instruction by itself
+fn note(pc: usize, instr: Instruction, instructions: &[Instruction]) -> Option<String> {
+    match instr {
+        JumpNotZero{ check: Literal(0), .. } => Some("never jumps".to_string()),
+        JumpNotZero{ delta: Register(_), .. } => Some("could jump anywhere".to_string()),
+        JumpNotZero{ .. } => match jump_target(pc, instr) {
+            Some(target) if !in_program(target, instructions) => Some("leaves the program".to_string()),
+            _ => None
+        },
+        Toggle{ reg: Register(_) } => Some("could toggle anything".to_string()),
+        Toggle{ reg: Literal(offset) } => Some(format!("toggles line {}", (pc as i64).saturating_add(offset))),
+        _ => None
+    }
+}
+
+// Prints the program with labels in place of jump offsets, line numbers, and notes on what each
+// optimised loop does. Everything besides the labels and instructions is a `;` comment, so the
+// output can be read back in by the assembler.
+pub fn disassemble<W: Write>(instructions: &[Instruction], out: &mut W) -> io::Result<()> {
+    let labels = labels(instructions);
+    let regions = optimised_regions(instructions);
+
+    for (pc, &instr) in instructions.iter().enumerate() {
+        for &(start, end, ref summary) in &regions {
+            if start == pc {
+                writeln!(out, "; lines {}-{} optimise to: {}", start, end - 1, summary)?;
+            }
+        }
+
+        let label = labels.get(&pc).map_or("".to_string(), |label| format!("{}:", label));
+        let text = format!("{:<6}{:<14}; {:>3}", label, with_label(pc, instr, &labels), pc);
+        match note(pc, instr, instructions) {
+            Some(note) => writeln!(out, "{}  {}", text, note)?,
+            None => writeln!(out, "{}", text)?
+        }
+    }
+    Ok(())
+}
+
+// Splits the program into basic blocks: runs of instructions which are only entered at the top and
+// only leave at the bottom. Returns the first line of each block.
+fn block_starts(instructions: &[Instruction]) -> BTreeSet<usize> {
+    let mut starts = BTreeSet::new();
+    starts.insert(0);
+    for (pc, &instr) in instructions.iter().enumerate() {
+        if let JumpNotZero{ .. } = instr {
+            starts.insert(pc + 1);
+        }
+        if let Some(target) = jump_target(pc, instr).filter(|&target| in_program(target, instructions)) {
+            starts.insert(target as usize);
+        }
+    }
+    starts.into_iter().filter(|&start| start < instructions.len()).collect()
+}
+
+fn node_name(target: i64, instructions: &[Instruction]) -> String {
+    if in_program(target, instructions) {
+        format!("b{}", target)
+    } else {
+        "end".to_string()
+    }
+}
+
+// Writes the control flow graph in Graphviz DOT format, with one node per basic block. Jumps by a
+// register could go anywhere, so they lead to a `?` node. Blocks making up a loop which the
+// optimiser replaces are grouped together, labelled with what the loop does.
+pub fn write_dot<W: Write>(instructions: &[Instruction], out: &mut W) -> io::Result<()> {
+    let labels = labels(instructions);
+    let regions = optimised_regions(instructions);
+    let starts:Vec<usize> = block_starts(instructions).into_iter().collect();
+
+    writeln!(out, "digraph assembunny {{")?;
+    writeln!(out, "    node [shape=box, fontname=monospace];")?;
+
+    let mut nodes = vec![];
+    let mut edges = vec![];
+    let mut uses_unknown = false;
+    for (i, &start) in starts.iter().enumerate() {
+        let end = starts.get(i + 1).cloned().unwrap_or(instructions.len());
+
+        let mut text = String::new();
+        if let Some(label) = labels.get(&start) {
+            text.push_str(&format!("{}:\\l", label));
+        }
+        for pc in start..end {
+            text.push_str(&format!("{:>3}  {}\\l", pc, with_label(pc, instructions[pc], &labels)));
+        }
+
+        nodes.push((start, format!("b{} [label=\"{}\"];", start, text)));
+
+        let last = end - 1;
+        let from = format!("b{}", start);
+        let falls_through = format!("    {} -> {};", from, node_name(end as i64, instructions));
+        match instructions[last] {
+            JumpNotZero{ check: Literal(0), .. } => edges.push(falls_through),
+            JumpNotZero{ check, delta: Register(_) } => {
+                uses_unknown = true;
+                if let Register(_) = check {
+                    edges.push(format!("    {} -> {} [label=\"{} == 0\"];", from, node_name(end as i64, instructions), check));
+                }
+                edges.push(format!("    {} -> unknown [style=dashed];", from));
+            },
+            JumpNotZero{ check, delta: Literal(delta) } => {
+                let target = node_name((last as i64).saturating_add(delta), instructions);
+                match check {
+                    Register(_) => {
+                        edges.push(format!("    {} -> {} [label=\"{} != 0\"];", from, target, check));
+                        edges.push(format!("    {} -> {} [label=\"{} == 0\"];", from, node_name(end as i64, instructions), check));
+                    },
+                    Literal(_) => edges.push(format!("    {} -> {};", from, target))
+                }
+            },
+            _ => edges.push(falls_through)
+        }
+    }
+
+    for &(start, ref node) in &nodes {
+        if !regions.iter().any(|&(region_start, region_end, _)| start >= region_start && start < region_end) {
+            writeln!(out, "    {}", node)?;
+        }
+    }
+    for &(region_start, region_end, ref summary) in &regions {
+        writeln!(out, "    subgraph cluster_{} {{", region_start)?;
+        writeln!(out, "        label=\"{}\";", summary)?;
+        writeln!(out, "        style=dashed;")?;
+        for &(start, ref node) in &nodes {
+            if start >= region_start && start < region_end {
+                writeln!(out, "        {}", node)?;
+            }
+        }
+        writeln!(out, "    }}")?;
+    }
+    writeln!(out, "    end [shape=doublecircle];")?;
+    if uses_unknown {
+        writeln!(out, "    unknown [label=\"?\", shape=circle];")?;
+    }
+    for edge in edges {
+        writeln!(out, "{}", edge)?;
+    }
+    writeln!(out, "}}")
+}
+
+#[cfg(test)]
+use ::parser::parse;
+
+#[cfg(test)]
+static MULTIPLY: &'static str = r"cpy 3 b
+cpy b c
+inc a
+dec c
+jnz c -2
+dec d
+jnz d -5
+jnz 1 c";
+
+#[test]
+fn disassembly_labels_jumps_and_notes_optimised_loops() {
+    let mut output = vec![];
+    disassemble(&parse(MULTIPLY).unwrap(), &mut output).unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), "      cpy 3 b       ;   0
+; lines 1-6 optimise to: a += d * b; c = 0; d = 0
+L1:   cpy b c       ;   1
+L2:   inc a         ;   2
+      dec c         ;   3
+      jnz c L2      ;   4
+      dec d         ;   5
+      jnz d L1      ;   6
+      jnz 1 c       ;   7  could jump anywhere
+");
+}
+
+#[test]
+fn dot_graph_groups_optimised_loops() {
+    let mut output = vec![];
+    write_dot(&parse(MULTIPLY).unwrap(), &mut output).unwrap();
+    let dot = String::from_utf8(output).unwrap();
+    assert!(dot.contains("    subgraph cluster_1 {\n        label=\"a += d * b; c = 0; d = 0\";"), "Got:\n{}", dot);
+    assert!(dot.contains("    b2 -> b2 [label=\"c != 0\"];\n    b2 -> b5 [label=\"c == 0\"];"), "Got:\n{}", dot);
+    assert!(dot.contains("    b7 -> unknown [style=dashed];"), "Got:\n{}", dot);
+}
@@ -0,0 +1,137 @@
+// Property tests checking the optimiser and compiler never change what a program does. Random
+// programs are run raw, optimised, and compiled on the same input, and must finish in the same
+// place with the same registers having output the same values. When a program doesn't, proptest
+// shrinks it down to the smallest one it can find which still fails, and that is what gets reported.
+
+use proptest::prelude::*;
+use proptest::sample::subsequence;
+
+use ::cpu::Cpu;
+use ::devices::{BufferedIo, Chain, StepBudget};
+use ::parser::Instruction;
+use ::parser::Instruction::*;
+use ::parser::Operand;
+use ::parser::Operand::*;
+
+// Raw runs taking longer than this are thrown away. It also keeps every value small enough that
+// an optimised loop can't overflow where the raw one wouldn't have.
+const STEP_BUDGET: usize = 2000;
+
+// The final registers, everything output and where the program stopped (past the end, or on an
+// `in` once the input ran out), or None if the program ran out of steps
+fn run(instructions: &[Instruction], regs: &[i64], input: &[i64], optimised: bool, compiled: bool) -> Option<(Vec<i64>, Vec<i64>, i32)> {
+    let io = BufferedIo::new(input.to_vec());
+    let outputs = io.outputs();
+    let env = Chain::new(vec![Box::new(io), Box::new(StepBudget::new(STEP_BUDGET, ()))]);
+    let mut cpu = Cpu::with_env(regs, Box::new(env));
+    cpu.set_optimised(optimised);
+    cpu.set_compiled(compiled);
+
+    if cpu.process(instructions.to_vec()).unwrap().is_some() {
+        return None;
+    }
+    let outputs = outputs.borrow().clone();
+    Some((cpu.regs.clone(), outputs, cpu.pc()))
+}
+
+fn listing(instructions: &[Instruction]) -> String {
+    instructions.iter()
+        .map(|instr| instr.to_string())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn register() -> BoxedStrategy<Operand> {
+    (0..4usize).prop_map(Register).boxed()
+}
+
+fn operand() -> BoxedStrategy<Operand> {
+    prop_oneof![
+        3 => register(),
+        1 => (-3..4i64).prop_map(Literal)
+    ].boxed()
+}
+
+fn instruction() -> BoxedStrategy<Instruction> {
+    prop_oneof![
+        3 => (operand(), register()).prop_map(|(source, target)| Copy{source: source, target: target}),
+        3 => register().prop_map(|reg| Inc{reg: reg}),
+        3 => register().prop_map(|reg| Dec{reg: reg}),
+        2 => (operand(), prop_oneof![register(), (-8..4i64).prop_map(Literal)])
+            .prop_map(|(check, delta)| JumpNotZero{check: check, delta: delta}),
+        1 => operand().prop_map(|reg| Toggle{reg: reg}),
+        1 => operand().prop_map(|operand| Out{operand: operand}),
+        1 => register().prop_map(|reg| In{reg: reg})
+    ].boxed()
+}
+
+// Random instructions rarely line up into a loop the optimiser can replace, so these are mixed in:
+// the counter is set up, then a body of incs, decs and copies is closed by a jnz on the counter.
+// The body comes out in any order and with any registers, so it may or may not be optimisable.
+fn counted_loop() -> BoxedStrategy<Vec<Instruction>> {
+    let body_instruction = prop_oneof![
+        register().prop_map(|reg| Inc{reg: reg}),
+        register().prop_map(|reg| Dec{reg: reg}),
+        (operand(), register()).prop_map(|(source, target)| Copy{source: source, target: target})
+    ];
+
+    (0..4usize, prop_oneof![register(), (1..5i64).prop_map(Literal)], prop::collection::vec(body_instruction, 0..4))
+        .prop_flat_map(|(counter, start, body)| {
+            let mut body = body;
+            body.push(Dec{reg: Register(counter)});
+            let len = body.len();
+            (Just(counter), Just(start), subsequence(body.clone(), len).prop_shuffle())
+        })
+        .prop_map(|(counter, start, body)| {
+            let mut instructions = vec![Copy{source: start, target: Register(counter)}];
+            let delta = -(body.len() as i64);
+            instructions.extend(body);
+            instructions.push(JumpNotZero{check: Register(counter), delta: Literal(delta)});
+            instructions
+        })
+        .boxed()
+}
+
+// Values for `in` to read, sometimes fewer than the program asks for
+fn input() -> BoxedStrategy<Vec<i64>> {
+    prop::collection::vec(-3..4i64, 0..4).boxed()
+}
+
+fn program() -> BoxedStrategy<Vec<Instruction>> {
+    let chunk = prop_oneof![
+        3 => instruction().prop_map(|instr| vec![instr]),
+        1 => counted_loop()
+    ];
+    prop::collection::vec(chunk, 1..12)
+        .prop_map(|chunks| chunks.into_iter().flatten().collect())
+        .boxed()
+}
+
+proptest! {
+    // Plenty of random programs never finish, so many more are thrown away than proptest expects
+    #![proptest_config(ProptestConfig { cases: 1000, max_global_rejects: 4000, ..ProptestConfig::default() })]
+
+    #[test]
+    fn optimised_programs_behave_the_same_as_raw_ones(instructions in program(), regs in prop::array::uniform4(0..5i64),
+                                                      input in input()) {
+        let raw = run(&instructions, &regs, &input, false, false);
+        prop_assume!(raw.is_some());
+
+        let optimised = run(&instructions, &regs, &input, true, false);
+        prop_assert_eq!(&raw, &optimised, "Program:\n{}\nStarting registers: {:?}\nInput: {:?}",
+                        listing(&instructions), regs, input);
+    }
+
+    #[test]
+    fn compiled_programs_behave_the_same_as_interpreted_ones(instructions in program(), regs in prop::array::uniform4(0..5i64),
+                                                             input in input(), optimised in any::<bool>()) {
+        // Only the raw run is slow enough to keep an optimised one from overflowing
+        prop_assume!(run(&instructions, &regs, &input, false, false).is_some());
+
+        let interpreted = run(&instructions, &regs, &input, optimised, false);
+
+        let compiled = run(&instructions, &regs, &input, optimised, true);
+        prop_assert_eq!(&interpreted, &compiled, "Program:\n{}\nStarting registers: {:?}\nInput: {:?}",
+                        listing(&instructions), regs, input);
+    }
+}
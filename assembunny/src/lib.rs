@@ -0,0 +1,36 @@
+extern crate regex;
+extern crate parse_error;
+extern crate serde;
+extern crate serde_json;
+
+#[macro_use]
+extern crate lazy_static;
+#[macro_use]
+extern crate serde_derive;
+#[cfg(test)]
+extern crate proptest;
+
+mod parser;
+mod assembler;
+mod optimiser;
+mod compiler;
+mod cpu;
+mod devices;
+mod debugger;
+mod disassembler;
+mod trace;
+mod replay;
+mod profiler;
+#[cfg(test)]
+mod fuzz;
+
+pub use parser::{DEFAULT_REGISTERS, Effect, Instruction, MAX_LOOP_EFFECTS, MAX_REGISTERS, Operand, parse, parse_with_registers};
+pub use assembler::{assemble, assemble_with_registers};
+pub use optimiser::optimise;
+pub use cpu::{Cpu, ExecutionEnvironment, Overflow, RunError, RunToEnd, Step, format_registers};
+pub use devices::{BufferedIo, Chain, StepBudget, StreamIo};
+pub use debugger::Debugger;
+pub use disassembler::{disassemble, write_dot};
+pub use trace::{TraceEvent, TraceStart, TraceStep, Toggled, read_trace};
+pub use replay::Replay;
+pub use profiler::Profile;
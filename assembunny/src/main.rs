@@ -0,0 +1,451 @@
+extern crate assembunny;
+
+use std::env;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read};
+use std::process;
+use std::time::{Duration, Instant};
+
+use assembunny::{Chain, Cpu, assemble, Debugger, DEFAULT_REGISTERS, ExecutionEnvironment, format_registers, Instruction, MAX_REGISTERS,
+                 Overflow, parse_with_registers, Replay, Step, StepBudget, StreamIo, disassemble, read_trace, write_dot};
+
+const USAGE: &'static str = "Usage: assembunny run <program> [--regs a,b,c,d] [--max-steps n]
+       assembunny debug <program> [--regs a,b,c,d] [--script <commands>]
+       assembunny trace <program> --out <trace> [--regs a,b,c,d] [--max-steps n] [--unoptimised]
+       assembunny replay <trace> [--script <commands>]
+       assembunny asm <source>
+       assembunny disasm <program> [--dot]
+       assembunny bench <program> [--regs a,b,c,d] [--runs n] [--unoptimised]
+       assembunny profile <program> [--regs a,b,c,d] [--max-steps n] [--unoptimised]
+
+--regs gives the starting values of a, b, c... Giving more than four (up to 26) lets the program use
+the extra registers e, f... as well.
+Registers are 64 bits, and anything which would overflow one stops the program with an error.
+run reads numbers for `in` from stdin and writes `out` values to stdout, one per line.
+Without a script, commands are read from stdin. Type 'help' for a list of them.
+Traces are JSON lines. Comparing a trace taken with --unoptimised against one without shows how
+the optimised loops diverge from the raw instructions.
+asm turns a program written with labels, constants and macros into plain assembunny.
+disasm prints the program with labelled jumps and notes on the optimised loops, or with --dot its
+control flow graph for Graphviz.
+bench times the interpreter against the compiled closures, averaged over a number of runs (10 by default).
+profile counts how often each instruction runs, and reports the hottest instructions and loops (and
+whether the loops were optimised), followed by stacks which flamegraph.pl can draw.";
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Run,
+    Debug,
+    Trace,
+    Replay,
+    Asm,
+    Disasm,
+    Bench,
+    Profile
+}
+
+#[derive(Debug, PartialEq)]
+struct Args {
+    command: Command,
+    file: String,
+    regs: Vec<i64>,
+    script: Option<String>,
+    out: Option<String>,
+    max_steps: Option<usize>,
+    runs: usize,
+    unoptimised: bool,
+    dot: bool
+}
+
+fn parse_regs(regs: &str) -> Result<Vec<i64>, String> {
+    let values = regs.split(',')
+        .map(|val| val.trim().parse::<i64>())
+        .collect::<Result<Vec<i64>, _>>()
+        .map_err(|_| format!("Registers must be numbers, but got: {}", regs))?;
+
+    if values.len() > MAX_REGISTERS {
+        return Err(format!("Expected at most {} register values, but got {}", MAX_REGISTERS, values.len()));
+    }
+    Ok(values)
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Result<Args, String> {
+        let mut args = args.iter();
+
+        let command = match args.next().map(|s| s.as_str()) {
+            Some("run") => Command::Run,
+            Some("debug") => Command::Debug,
+            Some("trace") => Command::Trace,
+            Some("replay") => Command::Replay,
+            Some("asm") => Command::Asm,
+            Some("disasm") => Command::Disasm,
+            Some("bench") => Command::Bench,
+            Some("profile") => Command::Profile,
+            Some(other) => return Err(format!("Unknown command: {}", other)),
+            None => return Err("No command given".to_string())
+        };
+
+        let file = match args.next() {
+            Some(file) => file.clone(),
+            None => return Err("No file given".to_string())
+        };
+
+        let mut regs = vec![0; DEFAULT_REGISTERS];
+        let mut script = None;
+        let mut out = None;
+        let mut max_steps = None;
+        let mut runs = 10;
+        let mut unoptimised = false;
+        let mut dot = false;
+        while let Some(arg) = args.next() {
+            match (arg.as_str(), &command) {
+                ("--regs", &Command::Run) | ("--regs", &Command::Debug) | ("--regs", &Command::Trace) | ("--regs", &Command::Bench) |
+                ("--regs", &Command::Profile) => {
+                    regs = match args.next() {
+                        Some(values) => parse_regs(values)?,
+                        None => return Err("No values given after --regs".to_string())
+                    };
+                },
+                ("--script", &Command::Debug) | ("--script", &Command::Replay) => {
+                    script = match args.next() {
+                        Some(path) => Some(path.clone()),
+                        None => return Err("No path given after --script".to_string())
+                    };
+                },
+                ("--out", &Command::Trace) => {
+                    out = match args.next() {
+                        Some(path) => Some(path.clone()),
+                        None => return Err("No path given after --out".to_string())
+                    };
+                },
+                ("--max-steps", &Command::Run) | ("--max-steps", &Command::Trace) | ("--max-steps", &Command::Profile) => {
+                    max_steps = match args.next().map(|n| n.parse::<usize>()) {
+                        Some(Ok(n)) => Some(n),
+                        Some(Err(_)) => return Err("--max-steps must be a number".to_string()),
+                        None => return Err("No number given after --max-steps".to_string())
+                    };
+                },
+                ("--runs", &Command::Bench) => {
+                    runs = match args.next().map(|n| n.parse::<usize>()) {
+                        Some(Ok(n)) if n > 0 => n,
+                        Some(_) => return Err("--runs must be a number above 0".to_string()),
+                        None => return Err("No number given after --runs".to_string())
+                    };
+                },
+                ("--unoptimised", &Command::Trace) | ("--unoptimised", &Command::Bench) | ("--unoptimised", &Command::Profile) => {
+                    unoptimised = true;
+                },
+                ("--dot", &Command::Disasm) => dot = true,
+                (other, _) => return Err(format!("Unexpected argument: {}", other))
+            }
+        }
+
+        if command == Command::Trace && out.is_none() {
+            return Err("trace needs an --out file".to_string());
+        }
+
+        Ok(Args {
+            command: command,
+            file: file,
+            regs: regs,
+            script: script,
+            out: out,
+            max_steps: max_steps,
+            runs: runs,
+            unoptimised: unoptimised,
+            dot: dot
+        })
+    }
+}
+
+fn read_file(path: &str) -> Result<String, String> {
+    let mut contents = String::new();
+    File::open(path)
+        .and_then(|mut file| file.read_to_string(&mut contents))
+        .map_err(|e| format!("Couldn't read {}: {}", path, e))?;
+    Ok(contents)
+}
+
+// Reads the program, allowing whichever registers were given values as well as a-d
+fn read_program(args: &Args) -> Result<Vec<Instruction>, String> {
+    let program = read_file(&args.file)?;
+    let num_registers = args.regs.len().max(DEFAULT_REGISTERS);
+    parse_with_registers(&program, num_registers).map_err(|e| format!("Couldn't parse {}: {}", args.file, e))
+}
+
+fn open_script(script: &Option<String>) -> Result<Option<BufReader<File>>, String> {
+    match *script {
+        Some(ref path) => File::open(path)
+            .map(|file| Some(BufReader::new(file)))
+            .map_err(|e| format!("Couldn't read {}: {}", path, e)),
+        None => Ok(None)
+    }
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let instructions = read_program(&args)?;
+
+    let mut envs:Vec<Box<dyn ExecutionEnvironment<()>>> = vec![Box::new(StreamIo::stdio())];
+    if let Some(max_steps) = args.max_steps {
+        envs.push(Box::new(StepBudget::new(max_steps, ())));
+    }
+
+    // stdout belongs to the program, so anything else goes to stderr
+    let mut cpu = Cpu::with_env(&args.regs, Box::new(Chain::new(envs)));
+    cpu.set_compiled(true);
+    let result = cpu.process(instructions);
+    if let Ok(Some(())) = result {
+        eprintln!("Stopped after {} steps", args.max_steps.unwrap());
+    }
+
+    eprintln!("{}", format_registers(&cpu.regs));
+    result.map(|_| ()).map_err(|overflow| overflow.to_string())
+}
+
+fn debug(args: Args) -> Result<(), String> {
+    let instructions = read_program(&args)?;
+    let script = open_script(&args.script)?;
+
+    let stdout = io::stdout();
+    let mut debugger = Debugger::new(instructions, &args.regs, stdout.lock());
+
+    let result = match script {
+        Some(script) => debugger.run(script, false),
+        None => {
+            let stdin = io::stdin();
+            let commands = stdin.lock();
+            debugger.run(commands, true)
+        }
+    };
+    result.map_err(|e| format!("Debugger I/O failed: {}", e))
+}
+
+fn trace(args: Args) -> Result<(), String> {
+    let instructions = read_program(&args)?;
+
+    let out_path = args.out.unwrap();
+    let out = File::create(&out_path).map_err(|e| format!("Couldn't create {}: {}", out_path, e))?;
+
+    let mut cpu = Cpu::new(&args.regs);
+    cpu.set_optimised(!args.unoptimised);
+    cpu.trace_to(Box::new(BufWriter::new(out)));
+    cpu.load(instructions);
+
+    // Programs like day 25's never finish by themselves
+    let mut steps = 0;
+    let mut overflow = None;
+    loop {
+        match cpu.step() {
+            Step::Running => {},
+            Step::Overflowed(o) => {
+                overflow = Some(o);
+                break;
+            },
+            Step::Finished | Step::Terminated(()) => break
+        }
+        steps += 1;
+        if Some(steps) == args.max_steps {
+            println!("Stopped after {} steps", steps);
+            break;
+        }
+    }
+    cpu.finish_trace().map_err(|e| format!("Couldn't write {}: {}", out_path, e))?;
+
+    println!("{}", format_registers(&cpu.regs));
+    match overflow {
+        Some(overflow) => Err(overflow.to_string()),
+        None => Ok(())
+    }
+}
+
+fn replay(args: Args) -> Result<(), String> {
+    let trace = File::open(&args.file).map_err(|e| format!("Couldn't read {}: {}", args.file, e))?;
+    let (start, steps) = read_trace(BufReader::new(trace)).map_err(|e| format!("Couldn't parse {}: {}", args.file, e))?;
+    let script = open_script(&args.script)?;
+
+    let mut replay = Replay::new(start, steps);
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    let result = match script {
+        Some(script) => replay.run(script, &mut out, false),
+        None => {
+            let stdin = io::stdin();
+            let commands = stdin.lock();
+            replay.run(commands, &mut out, true)
+        }
+    };
+    result.map_err(|e| format!("Replay I/O failed: {}", e))
+}
+
+fn asm(args: Args) -> Result<(), String> {
+    let source = read_file(&args.file)?;
+    let instructions = assemble(&source).map_err(|e| format!("Couldn't assemble {}: {}", args.file, e))?;
+
+    for instruction in instructions {
+        println!("{}", instruction);
+    }
+    Ok(())
+}
+
+fn disasm(args: Args) -> Result<(), String> {
+    let instructions = read_program(&args)?;
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let result = if args.dot {
+        write_dot(&instructions, &mut out)
+    } else {
+        disassemble(&instructions, &mut out)
+    };
+    result.map_err(|e| format!("Couldn't write the output: {}", e))
+}
+
+// Runs the program `runs` times, returning the final registers and the average time taken
+fn time_runs(instructions: &[Instruction], args: &Args, compiled: bool) -> Result<(Vec<i64>, Duration), Overflow> {
+    let start = Instant::now();
+    let mut regs = args.regs.clone();
+    for _ in 0..args.runs {
+        let mut cpu = Cpu::new(&args.regs);
+        cpu.set_optimised(!args.unoptimised);
+        cpu.set_compiled(compiled);
+        cpu.process(instructions.to_vec())?;
+        regs = cpu.regs;
+    }
+    Ok((regs, start.elapsed() / args.runs as u32))
+}
+
+fn bench(args: Args) -> Result<(), String> {
+    let instructions = read_program(&args)?;
+
+    let (interpreted_regs, interpreted) = time_runs(&instructions, &args, false).map_err(|overflow| overflow.to_string())?;
+    let (compiled_regs, compiled) = time_runs(&instructions, &args, true).map_err(|overflow| overflow.to_string())?;
+    if interpreted_regs != compiled_regs {
+        return Err(format!("Compiled run finished with {:?}, but the interpreter finished with {:?}",
+                           compiled_regs, interpreted_regs));
+    }
+
+    println!("{}", format_registers(&compiled_regs));
+    println!("Interpreted: {:?}", interpreted);
+    println!("Compiled:    {:?}", compiled);
+    println!("Speedup:     {:.1}x", interpreted.as_secs_f64() / compiled.as_secs_f64().max(1e-9));
+    Ok(())
+}
+
+fn profile(args: Args) -> Result<(), String> {
+    let instructions = read_program(&args)?;
+
+    // Output and input aren't needed to see where the time goes
+    let mut cpu = match args.max_steps {
+        Some(max_steps) => Cpu::with_env(&args.regs, Box::new(StepBudget::new(max_steps, ()))),
+        None => Cpu::new(&args.regs)
+    };
+    cpu.set_optimised(!args.unoptimised);
+    cpu.set_profiled(true);
+    let result = cpu.process(instructions);
+    if let Ok(Some(())) = result {
+        println!("Stopped after {} steps", args.max_steps.unwrap());
+    }
+    println!("{}", format_registers(&cpu.regs));
+    println!();
+
+    let stdout = io::stdout();
+    cpu.profile().unwrap()
+        .write_report(cpu.unoptimised_instructions(), cpu.instructions(), &mut stdout.lock())
+        .map_err(|e| format!("Couldn't write the report: {}", e))?;
+    result.map(|_| ()).map_err(|overflow| overflow.to_string())
+}
+
+fn main() {
+    let args:Vec<String> = env::args().skip(1).collect();
+
+    let args = match Args::parse(&args) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{}", message);
+            eprintln!("{}", USAGE);
+            process::exit(1);
+        }
+    };
+
+    let result = match args.command {
+        Command::Run => run(args),
+        Command::Debug => debug(args),
+        Command::Trace => trace(args),
+        Command::Replay => replay(args),
+        Command::Asm => asm(args),
+        Command::Disasm => disasm(args),
+        Command::Bench => bench(args),
+        Command::Profile => profile(args)
+    };
+    if let Err(message) = result {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+fn to_args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn debug_with_regs_and_script_parses() {
+    assert_eq!(
+        Args::parse(&to_args(&["debug", "day23.txt", "--regs", "7,0,0,0", "--script", "cmds.txt"])),
+        Ok(Args {
+            command: Command::Debug,
+            file: "day23.txt".to_string(),
+            regs: vec![7, 0, 0, 0],
+            script: Some("cmds.txt".to_string()),
+            out: None,
+            max_steps: None,
+            runs: 10,
+            unoptimised: false,
+            dot: false
+        })
+    );
+}
+
+#[test]
+fn regs_can_be_given_for_up_to_26_registers() {
+    let regs = |values: &str| Args::parse(&to_args(&["debug", "day23.txt", "--regs", values])).map(|args| args.regs);
+    assert_eq!(regs("7,0"), Ok(vec![7, 0]));
+    assert_eq!(regs("1,2,3,4,5,6"), Ok(vec![1, 2, 3, 4, 5, 6]));
+    assert!(regs(&vec!["0"; 27].join(",")).is_err());
+    assert!(regs("7,x").is_err());
+}
+
+#[test]
+fn trace_needs_an_out_file() {
+    assert!(Args::parse(&to_args(&["trace", "day23.txt"])).is_err());
+    assert!(Args::parse(&to_args(&["trace", "day23.txt", "--out", "t.jsonl", "--unoptimised"])).is_ok());
+}
+
+#[test]
+fn options_for_other_commands_are_rejected() {
+    assert!(Args::parse(&to_args(&["replay", "t.jsonl", "--regs", "1,2,3,4"])).is_err());
+    assert!(Args::parse(&to_args(&["debug", "day23.txt", "--out", "t.jsonl"])).is_err());
+    assert!(Args::parse(&to_args(&["debug", "day23.txt", "--runs", "3"])).is_err());
+}
+
+#[test]
+fn run_takes_regs_and_max_steps() {
+    let args = Args::parse(&to_args(&["run", "echo.txt", "--regs", "1,2,3,4", "--max-steps", "100"])).unwrap();
+    assert_eq!((args.command, args.regs, args.max_steps), (Command::Run, vec![1, 2, 3, 4], Some(100)));
+    assert!(Args::parse(&to_args(&["run", "echo.txt", "--script", "cmds.txt"])).is_err());
+}
+
+#[test]
+fn bench_needs_a_positive_number_of_runs() {
+    assert_eq!(Args::parse(&to_args(&["bench", "day12.txt", "--runs", "3"])).map(|args| args.runs), Ok(3));
+    assert!(Args::parse(&to_args(&["bench", "day12.txt", "--runs", "0"])).is_err());
+}
+
+#[test]
+fn profile_takes_regs_max_steps_and_unoptimised() {
+    let args = Args::parse(&to_args(&["profile", "day23.txt", "--regs", "7", "--max-steps", "1000", "--unoptimised"])).unwrap();
+    assert_eq!((args.command, args.regs, args.max_steps, args.unoptimised), (Command::Profile, vec![7], Some(1000), true));
+    assert!(Args::parse(&to_args(&["profile", "day23.txt", "--runs", "3"])).is_err());
+}
@@ -0,0 +1,420 @@
+use ::parser::{Effect, Instruction, MAX_LOOP_EFFECTS, MAX_REGISTERS};
+use ::parser::Instruction::*;
+use ::parser::Operand;
+use ::parser::Operand::*;
+
+// Rather than matching exact instruction sequences, every loop closed by a backward `jnz` is run
+// symbolically, tracking each register as a linear function of the registers at the start of an
+// iteration (the data flow idea sketched in data-flow.txt). Any ordering of the same instructions
+// gives the same functions, so add, multiply and clear loops are all recognised whichever way
+// round they are written and whichever registers they use.
+//
+// A loop can be replaced when its counter goes down by exactly one each time round, and every
+// other register is either left alone, has something added which doesn't change during the loop,
+// or is set to something which doesn't change during the loop. Inner loops are summarised first,
+// so an outer loop just sees their effects (which is how multiplication falls out of an add loop).
+//
+// As before, loops are assumed to start with a positive counter. Otherwise the real loop would
+// spin until the counter wraps around, which no sensible program relies on.
+
+// constant + sum(coefficients[r] * reg[r]), with registers as they were at the start of the loop
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Linear {
+    constant: i64,
+    coefficients: [i64; MAX_REGISTERS]
+}
+
+impl Linear {
+    fn constant(constant: i64) -> Linear {
+        Linear {
+            constant: constant,
+            coefficients: [0; MAX_REGISTERS]
+        }
+    }
+
+    fn register(reg_idx: usize) -> Linear {
+        let mut coefficients = [0; MAX_REGISTERS];
+        coefficients[reg_idx] = 1;
+        Linear {
+            constant: 0,
+            coefficients: coefficients
+        }
+    }
+
+    fn of(operand: Operand, state: &[Linear; MAX_REGISTERS]) -> Linear {
+        match operand {
+            Register(reg_idx) => state[reg_idx],
+            Literal(value) => Linear::constant(value)
+        }
+    }
+
+    fn is_constant(&self) -> bool {
+        self.coefficients.iter().all(|&c| c == 0)
+    }
+
+    // Arithmetic gives up (None) on overflow, leaving the loop unoptimised
+    fn add(&self, other: &Linear) -> Option<Linear> {
+        let mut result = Linear::constant(self.constant.checked_add(other.constant)?);
+        for reg_idx in 0..MAX_REGISTERS {
+            result.coefficients[reg_idx] = self.coefficients[reg_idx].checked_add(other.coefficients[reg_idx])?;
+        }
+        Some(result)
+    }
+
+    fn scale(&self, factor: i64) -> Option<Linear> {
+        let mut result = Linear::constant(self.constant.checked_mul(factor)?);
+        for reg_idx in 0..MAX_REGISTERS {
+            result.coefficients[reg_idx] = self.coefficients[reg_idx].checked_mul(factor)?;
+        }
+        Some(result)
+    }
+
+    // Only a constant times something linear stays linear
+    fn multiply(&self, other: &Linear) -> Option<Linear> {
+        if self.is_constant() {
+            other.scale(self.constant)
+        } else if other.is_constant() {
+            self.scale(other.constant)
+        } else {
+            None
+        }
+    }
+
+    // Turns `constant` or `coefficient * reg` into (scale, operand), if it is that simple
+    fn as_single_term(&self) -> Option<(i64, Operand)> {
+        let terms:Vec<usize> = (0..MAX_REGISTERS).filter(|&r| self.coefficients[r] != 0).collect();
+        match (terms.len(), self.constant) {
+            (0, constant) => Some((constant, Literal(1))),
+            (1, 0) => Some((self.coefficients[terms[0]], Register(terms[0]))),
+            _ => None
+        }
+    }
+}
+
+// The loop made up of instructions start..=end, closed by a `jnz counter` at end
+struct LoopSummary {
+    start: usize,
+    end: usize,
+    counter: usize,
+    effects: [Effect; MAX_LOOP_EFFECTS]
+}
+
+// Every backward jump by a literal amount on a register, as (start, end, counter)
+fn find_loops(instructions: &[Instruction]) -> Vec<(usize, usize, usize)> {
+    let mut loops = vec![];
+    for (end, instr) in instructions.iter().enumerate() {
+        if let JumpNotZero{check: Register(counter), delta: Literal(delta)} = *instr {
+            if delta < 0 && end as i64 + delta >= 0 {
+                loops.push(((end as i64 + delta) as usize, end, counter));
+            }
+        }
+    }
+    loops
+}
+
+// Runs the loop body symbolically, giving what one time round the loop does to each register
+fn run_body(instructions: &[Instruction], start: usize, end: usize, inner: &[LoopSummary]) -> Option<[Linear; MAX_REGISTERS]> {
+    let mut state = [Linear::constant(0); MAX_REGISTERS];
+    for (reg_idx, value) in state.iter_mut().enumerate() {
+        *value = Linear::register(reg_idx);
+    }
+
+    let mut pc = start;
+    while pc < end {
+        // The longest inner loop starting here, if any
+        let inner_loop = inner.iter()
+            .filter(|l| l.start == pc && l.end < end)
+            .max_by_key(|l| l.end);
+
+        if let Some(inner_loop) = inner_loop {
+            let times = state[inner_loop.counter];
+            let mut new_state = state;
+            for effect in inner_loop.effects.iter() {
+                match *effect {
+                    Effect::Unchanged => {},
+                    Effect::Add{reg, factor, scale} => {
+                        let added = times.multiply(&Linear::of(factor, &state))?.scale(scale)?;
+                        new_state[reg] = state[reg].add(&added)?;
+                    },
+                    Effect::Set{reg, source} => new_state[reg] = Linear::of(source, &state)
+                }
+            }
+            state = new_state;
+            pc = inner_loop.end + 1;
+            continue;
+        }
+
+        match instructions[pc] {
+            Inc{reg: Register(reg_idx)} => state[reg_idx] = state[reg_idx].add(&Linear::constant(1))?,
+            Dec{reg: Register(reg_idx)} => state[reg_idx] = state[reg_idx].add(&Linear::constant(-1))?,
+            Copy{source, target: Register(reg_idx)} => state[reg_idx] = Linear::of(source, &state),
+            // Instructions left nonsensical by a tgl are skipped when run
+            Inc{reg: Literal(_)} | Dec{reg: Literal(_)} | Copy{target: Literal(_), ..} => {},
+            // Anything else (other jumps, toggles, input or output) means we can't predict the loop
+            _ => return None
+        }
+        pc += 1;
+    }
+
+    Some(state)
+}
+
+fn summarise(instructions: &[Instruction], start: usize, end: usize, counter: usize, inner: &[LoopSummary]) -> Option<LoopSummary> {
+    let state = run_body(instructions, start, end, inner)?;
+
+    // The counter must go down by one each time round...
+    if state[counter] != Linear::register(counter).add(&Linear::constant(-1))? {
+        return None;
+    }
+
+    // ...and everything else has to be worked out from registers the loop doesn't change
+    let unchanged:Vec<bool> = (0..MAX_REGISTERS).map(|r| state[r] == Linear::register(r)).collect();
+    let uses_only_unchanged = |value: &Linear| (0..MAX_REGISTERS).all(|r| value.coefficients[r] == 0 || unchanged[r]);
+
+    let mut effects = vec![];
+    for reg_idx in 0..MAX_REGISTERS {
+        if reg_idx == counter {
+            effects.push(Effect::Set{reg: reg_idx, source: Literal(0)});
+        } else if unchanged[reg_idx] {
+            continue;
+        } else if state[reg_idx].coefficients[reg_idx] == 1 {
+            // Added to each time round
+            let added = state[reg_idx].add(&Linear::register(reg_idx).scale(-1)?)?;
+            if !uses_only_unchanged(&added) {
+                return None;
+            }
+            let (scale, factor) = added.as_single_term()?;
+            effects.push(Effect::Add{reg: reg_idx, factor: factor, scale: scale});
+        } else if state[reg_idx].coefficients[reg_idx] == 0 && uses_only_unchanged(&state[reg_idx]) {
+            // Set to the same thing each time round
+            effects.push(match state[reg_idx].as_single_term()? {
+                (constant, Literal(1)) => Effect::Set{reg: reg_idx, source: Literal(constant)},
+                (1, source) => Effect::Set{reg: reg_idx, source: source},
+                _ => return None
+            });
+        } else {
+            return None;
+        }
+    }
+
+    // Loops which change more registers than fit in a Loop instruction are left as they are
+    if effects.len() > MAX_LOOP_EFFECTS {
+        return None;
+    }
+    let mut effect_slots = [Effect::Unchanged; MAX_LOOP_EFFECTS];
+    effect_slots[..effects.len()].copy_from_slice(&effects);
+
+    Some(LoopSummary {
+        start: start,
+        end: end,
+        counter: counter,
+        effects: effect_slots
+    })
+}
+
+// Whether a jump from outside the loop lands part way through it, where the Nops will be
+fn jumped_into(instructions: &[Instruction], start: usize, end: usize) -> bool {
+    instructions.iter().enumerate().any(|(idx, instr)| {
+        if let JumpNotZero{delta: Literal(delta), ..} = *instr {
+            let target = (idx as i64).saturating_add(delta);
+            (idx < start || idx > end) && target > start as i64 && target <= end as i64
+        } else {
+            false
+        }
+    })
+}
+
+pub fn optimise(instructions: &[Instruction]) -> Vec<Instruction> {
+    // Summarise the innermost loops first, so the loops around them can use their effects
+    let mut loops = find_loops(instructions);
+    loops.sort_by_key(|&(start, end, _)| end - start);
+
+    let mut summaries = vec![];
+    for (start, end, counter) in loops {
+        if let Some(summary) = summarise(instructions, start, end, counter, &summaries) {
+            summaries.push(summary);
+        }
+    }
+
+    // Replace the outermost loops, which already include whatever is inside them
+    summaries.reverse();
+    let mut optimised = instructions.to_vec();
+    let mut replaced = vec![false; instructions.len()];
+    for summary in summaries {
+        if replaced[summary.start..=summary.end].iter().any(|&r| r) ||
+            jumped_into(instructions, summary.start, summary.end) {
+            continue;
+        }
+
+        optimised[summary.start] = Loop {
+            counter: summary.counter,
+            effects: summary.effects,
+            len: summary.end - summary.start + 1
+        };
+        for idx in (summary.start + 1)..=summary.end {
+            optimised[idx] = Nop;
+        }
+        for idx in summary.start..=summary.end {
+            replaced[idx] = true;
+        }
+    }
+
+    optimised
+}
+
+// Runs an optimised loop on the registers, giving None (with the registers untouched) if any of
+// them would overflow. Every effect is in terms of the registers before the loop.
+pub fn run_loop(counter: usize, effects: &[Effect], regs: &mut [i64]) -> Option<()> {
+    let times = regs[counter];
+    let value = |operand, regs: &[i64]| match operand {
+        Register(reg_idx) => regs[reg_idx],
+        Literal(value) => value
+    };
+
+    let mut results = [(0, 0); MAX_LOOP_EFFECTS];
+    for (result, effect) in results.iter_mut().zip(effects.iter()) {
+        *result = match *effect {
+            Effect::Unchanged => continue,
+            Effect::Add{reg, factor, scale} => {
+                let added = times.checked_mul(scale)?.checked_mul(value(factor, regs))?;
+                (reg, regs[reg].checked_add(added)?)
+            },
+            Effect::Set{reg, source} => (reg, value(source, regs))
+        };
+    }
+
+    for (effect, &(reg, result)) in effects.iter().zip(results.iter()) {
+        if *effect != Effect::Unchanged {
+            regs[reg] = result;
+        }
+    }
+    Some(())
+}
+
+#[cfg(test)]
+use ::parser::parse;
+
+#[cfg(test)]
+fn loop_effects(instructions: &[Instruction]) -> (usize, [Effect; MAX_LOOP_EFFECTS]) {
+    match instructions[0] {
+        Loop{counter, effects, len} => {
+            assert!(instructions[1..len].iter().all(|&i| i == Nop));
+            (counter, effects)
+        },
+        other => panic!("Expected a loop, but got {:?}", other)
+    }
+}
+
+#[test]
+fn multiply_optimise_matches_instructions_which_multiply_two_registers() {
+    // source: 0, add_drain: 1, out: 2, mult_drain: 3
+    let instructions = [
+        Copy{source: Register(0), target: Register(1)},
+        Inc{reg: Register(2)},
+        Dec{reg: Register(1)},
+        JumpNotZero{check: Register(1), delta: Literal(-2)},
+        Dec{reg: Register(3)},
+        JumpNotZero{check: Register(3), delta: Literal(-5)}
+    ];
+    let (counter, effects) = loop_effects(&optimise(&instructions));
+    assert_eq!(counter, 3);
+    assert_eq!(effects, [
+        Effect::Set{reg: 1, source: Literal(0)},
+        Effect::Add{reg: 2, factor: Register(0), scale: 1},
+        Effect::Set{reg: 3, source: Literal(0)},
+        Effect::Unchanged
+    ]);
+}
+
+#[test]
+fn optimise_matches_instructions_which_multiply_two_registers_and_adds_noops() {
+    // source: 0, add_drain: 1, out: 2, mult_drain: 3
+    let instructions = [
+        Copy{source: Register(0), target: Register(1)},
+        Inc{reg: Register(2)},
+        Dec{reg: Register(1)},
+        JumpNotZero{check: Register(1), delta: Literal(-2)},
+        Dec{reg: Register(3)},
+        JumpNotZero{check: Register(3), delta: Literal(-5)},
+        Dec{reg: Register(5)}
+    ];
+    let optimised = optimise(&instructions);
+    assert!(matches!(&optimised[..], &[Loop{..}, Nop, Nop, Nop, Nop, Nop, Dec{..}]));
+}
+
+#[test]
+fn optimise_replaces_add() {
+    let file = r"cpy c d
+dec d
+inc c
+jnz d -2
+tgl c";
+    let instructions = optimise(&parse(file).unwrap());
+    let matches = matches!(&instructions[..], &[Copy{..}, Loop{..}, Nop, Nop, Toggle{..}]);
+    assert!(matches, "Should be [Copy, Loop, Nop, Nop, Toggle] but got {:?}", instructions);
+}
+
+#[test]
+fn reordered_multiply_with_other_registers_is_recognised() {
+    // d += 4 * c * b, written differently to the puzzle input
+    let file = r"dec b
+cpy c a
+inc d
+dec a
+inc d
+inc d
+inc d
+jnz a -5
+jnz b -8";
+    let (counter, effects) = loop_effects(&optimise(&parse(file).unwrap()));
+    assert_eq!(counter, 1);
+    assert_eq!(effects, [
+        Effect::Set{reg: 0, source: Literal(0)},
+        Effect::Set{reg: 1, source: Literal(0)},
+        Effect::Add{reg: 3, factor: Register(2), scale: 4},
+        Effect::Unchanged
+    ]);
+}
+
+#[test]
+fn clear_loop_is_recognised() {
+    let (counter, effects) = loop_effects(&optimise(&parse("dec c\njnz c -1").unwrap()));
+    assert_eq!(counter, 2);
+    assert_eq!(effects, [Effect::Set{reg: 2, source: Literal(0)}, Effect::Unchanged, Effect::Unchanged, Effect::Unchanged]);
+}
+
+#[test]
+fn loops_which_depend_on_themselves_are_left_alone() {
+    // a doubles each time round, which isn't linear in the counter
+    let file = r"cpy a c
+inc a
+dec c
+jnz c -2
+dec b
+jnz b -5";
+    let optimised = optimise(&parse(file).unwrap());
+    assert!(matches!(&optimised[..], &[Copy{..}, Loop{..}, Nop, Nop, Dec{..}, JumpNotZero{..}]),
+            "Should only optimise the inner loop, but got {:?}", optimised);
+}
+
+#[test]
+fn loops_jumped_into_from_outside_are_left_alone() {
+    let file = r"jnz 1 3
+inc a
+dec b
+jnz b -2";
+    let optimised = optimise(&parse(file).unwrap());
+    assert!(!optimised.contains(&Nop), "Got {:?}", optimised);
+}
+
+#[test]
+fn loops_changing_too_many_registers_are_left_alone() {
+    let file = r"inc a
+inc b
+inc c
+inc e
+dec d
+jnz d -5";
+    let optimised = optimise(&::parser::parse_with_registers(file, 5).unwrap());
+    assert!(!optimised.contains(&Nop), "Got {:?}", optimised);
+}
@@ -0,0 +1,246 @@
+use parser::Operand::*;
+use parser::Instruction::*;
+
+use regex::{Captures, Regex};
+
+use std::fmt;
+
+use parse_error::ParseError;
+
+lazy_static! {
+    static ref CPY_RE:Regex = Regex::new(r"^cpy (\S+) (\S+)$").unwrap();
+    static ref INC_RE:Regex = Regex::new(r"^inc (\S+)$").unwrap();
+    static ref DEC_RE:Regex = Regex::new(r"^dec (\S+)$").unwrap();
+    static ref JNZ_RE:Regex = Regex::new(r"^jnz (\S+) (\S+)$").unwrap();
+    static ref TGL_RE:Regex = Regex::new(r"^tgl (\S+)$").unwrap();
+    static ref OUT_RE:Regex = Regex::new(r"^out (\S+)$").unwrap();
+    static ref IN_RE:Regex = Regex::new(r"^in (\S+)$").unwrap();
+}
+
+// Programs use registers a-d unless told otherwise, and can have up to a-z
+pub const DEFAULT_REGISTERS: usize = 4;
+pub const MAX_REGISTERS: usize = 26;
+
+// An optimised loop can change at most this many registers (counter included)
+pub const MAX_LOOP_EFFECTS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operand {
+    Register(usize),
+    Literal(i64)
+}
+
+// "a-d" for the default four registers
+fn register_range(num_registers: usize) -> String {
+    format!("a-{}", (b'a' + num_registers as u8 - 1) as char)
+}
+
+impl Operand {
+    pub fn parse(str: &str) -> Result<Operand, String> {
+        Operand::parse_with_registers(str, DEFAULT_REGISTERS)
+    }
+
+    // Accepts the first num_registers letters as registers, so 6 gives a-f
+    pub fn parse_with_registers(str: &str, num_registers: usize) -> Result<Operand, String> {
+        assert!((1..=MAX_REGISTERS).contains(&num_registers), "Can't have {} registers", num_registers);
+
+        let bytes = str.as_bytes();
+        if bytes.len() == 1 && bytes[0] >= b'a' && ((bytes[0] - b'a') as usize) < num_registers {
+            return Ok(Register((bytes[0] - b'a') as usize));
+        }
+        str.parse::<i64>()
+            .map(Literal)
+            .map_err(|_| format!("Expected a register ({}) or a number", register_range(num_registers)))
+    }
+}
+
+// Parses the operand in the given capture group, reporting where it is in the line if it's bad
+fn operand(caps: &Captures, group: usize, line_num: usize, line: &str, num_registers: usize) -> Result<Operand, ParseError> {
+    let text = caps.at(group).unwrap();
+    Operand::parse_with_registers(text, num_registers).map_err(|message| ParseError::at(line_num, line, text, &message))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Instruction {
+    Copy{source:Operand, target:Operand},
+    Inc{reg:Operand},
+    Dec{reg:Operand},
+    JumpNotZero{check:Operand, delta:Operand},
+    Toggle{reg:Operand},
+    Out{operand:Operand},
+    // Reads a value from the execution environment into a register
+    In{reg:Operand},
+    // Stands in for a whole loop of `len` instructions, the rest of which are replaced by Nops.
+    // The loop runs `counter` times, and effects are what that does to the registers it changes.
+    Loop{counter:usize, effects:[Effect; MAX_LOOP_EFFECTS], len:usize},
+    Nop
+}
+
+// What a whole optimised loop does to a register, in terms of the values before the loop started
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Effect {
+    // Fills the slots left over by loops which change fewer registers
+    Unchanged,
+    // reg += counter * scale * factor
+    Add{reg:usize, factor:Operand, scale:i64},
+    // reg = source
+    Set{reg:usize, source:Operand}
+}
+
+impl Instruction {
+    // Parses a single line of the program, where line_num is only used for error reporting
+    pub fn parse(line_num: usize, line: &str) -> Result<Instruction, ParseError> {
+        Instruction::parse_with_registers(line_num, line, DEFAULT_REGISTERS)
+    }
+
+    pub fn parse_with_registers(line_num: usize, line: &str, num_registers: usize) -> Result<Instruction, ParseError> {
+        let line = line.trim_end();
+        let instruction = if let Some(caps) = CPY_RE.captures(line) {
+            let val_or_reg = operand(&caps, 1, line_num, line, num_registers)?;
+            let target_reg = operand(&caps, 2, line_num, line, num_registers)?;
+            Copy{source: val_or_reg, target: target_reg}
+        } else if let Some(caps) = INC_RE.captures(line) {
+            let reg = operand(&caps, 1, line_num, line, num_registers)?;
+            Inc{reg: reg}
+        } else if let Some(caps) = DEC_RE.captures(line) {
+            let reg = operand(&caps, 1, line_num, line, num_registers)?;
+            Dec{reg: reg}
+        } else if let Some(caps) = JNZ_RE.captures(line) {
+            let val_or_reg = operand(&caps, 1, line_num, line, num_registers)?;
+            let delta = operand(&caps, 2, line_num, line, num_registers)?;
+            JumpNotZero{check: val_or_reg, delta: delta}
+        } else if let Some(caps) = TGL_RE.captures(line) {
+            let reg = operand(&caps, 1, line_num, line, num_registers)?;
+            Toggle{reg: reg}
+        } else if let Some(caps) = OUT_RE.captures(line) {
+            let val_or_reg = operand(&caps, 1, line_num, line, num_registers)?;
+            Out{operand: val_or_reg}
+        } else if let Some(caps) = IN_RE.captures(line) {
+            let reg = operand(&caps, 1, line_num, line, num_registers)?;
+            if let Literal(_) = reg {
+                let message = format!("Expected a register ({}) to read into", register_range(num_registers));
+                return Err(ParseError::at(line_num, line, caps.at(1).unwrap(), &message));
+            }
+            In{reg: reg}
+        } else {
+            return Err(ParseError::new(line_num, 1, line, "Did not recognise instruction"));
+        };
+
+        Ok(instruction)
+    }
+
+    pub fn toggle(&self) -> Instruction {
+        match *self {
+            Copy{source, target} => {
+                JumpNotZero{check: source, delta: target}
+            },
+            Inc{reg} => {
+                Dec{reg: reg}
+            },
+            Dec{reg} => {
+                Inc{reg: reg}
+            },
+            JumpNotZero{check, delta} => {
+                Copy{source: check, target: delta}
+            },
+            Toggle{reg} => {
+                Inc { reg: reg }
+            },
+            Out{operand} => {
+                Inc { reg: operand }
+            },
+            In{reg} => {
+                Inc { reg: reg }
+            },
+            // The CPU toggles the original instructions and optimises them again, so never these
+            Loop{..} | Nop => {
+                unreachable!("Trying to toggle an optimised instruction")
+            }
+        }
+    }
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Register(reg_idx) => write!(f, "{}", (b'a' + reg_idx as u8) as char),
+            Literal(value) => write!(f, "{}", value)
+        }
+    }
+}
+
+// Shows instructions as they'd be written in the program. The optimised ones aren't real
+// assembunny, so they're written out as what they do instead.
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Copy{source, target} => write!(f, "cpy {} {}", source, target),
+            Inc{reg} => write!(f, "inc {}", reg),
+            Dec{reg} => write!(f, "dec {}", reg),
+            JumpNotZero{check, delta} => write!(f, "jnz {} {}", check, delta),
+            Toggle{reg} => write!(f, "tgl {}", reg),
+            Out{operand} => write!(f, "out {}", operand),
+            In{reg} => write!(f, "in {}", reg),
+            Loop{counter, effects, ..} => {
+                let counter = Register(counter);
+                let mut parts = vec![];
+                for effect in effects.iter() {
+                    match *effect {
+                        Effect::Unchanged => {},
+                        Effect::Add{reg, factor, scale} => {
+                            let scale = if scale == 1 { "".to_string() } else { format!("{} * ", scale) };
+                            let factor = if factor == Literal(1) { "".to_string() } else { format!(" * {}", factor) };
+                            parts.push(format!("{} += {}{}{}", Register(reg), scale, counter, factor));
+                        },
+                        Effect::Set{reg, source} => parts.push(format!("{} = {}", Register(reg), source))
+                    }
+                }
+                write!(f, "{}", parts.join("; "))
+            },
+            Nop => write!(f, "nop")
+        }
+    }
+}
+
+pub fn parse(file: &str) -> Result<Vec<Instruction>, ParseError> {
+    parse_with_registers(file, DEFAULT_REGISTERS)
+}
+
+pub fn parse_with_registers(file: &str, num_registers: usize) -> Result<Vec<Instruction>, ParseError> {
+    file.lines().enumerate()
+        .map(|(i, line)| Instruction::parse_with_registers(i + 1, line, num_registers))
+        .collect()
+}
+
+#[test]
+fn bad_operand_reports_line_and_column() {
+    let file = r"cpy 2 a
+tgl a
+cpy 1 e";
+    assert_eq!(parse(file).unwrap_err(), ParseError::new(3, 7, "e", "Expected a register (a-d) or a number"));
+}
+
+#[test]
+fn extra_registers_have_to_be_asked_for() {
+    assert_eq!(parse_with_registers("cpy 1 e\ninc f", 6).unwrap(), vec![
+        Copy{source: Literal(1), target: Register(4)},
+        Inc{reg: Register(5)}
+    ]);
+    assert_eq!(parse_with_registers("dec g", 6).unwrap_err(), ParseError::new(1, 5, "g", "Expected a register (a-f) or a number"));
+}
+
+#[test]
+fn instructions_display_as_written() {
+    let file = r"cpy 41 a
+jnz c -2
+tgl b
+out d
+in a";
+    let displayed:Vec<String> = parse(file).unwrap().iter().map(|i| i.to_string()).collect();
+    assert_eq!(displayed.join("\n"), file);
+}
+
+#[test]
+fn in_needs_a_register() {
+    assert_eq!(parse("in 3").unwrap_err(), ParseError::new(1, 4, "3", "Expected a register (a-d) to read into"));
+}
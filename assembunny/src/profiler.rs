@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use ::parser::Instruction;
+use ::parser::Instruction::*;
+use ::parser::Operand::*;
+
+// Counts how often each instruction runs, to show where a program spends its time. An optimised
+// loop runs as one instruction, so its runs are counted separately along with how many times round
+// the loop they saved. Hot loops which aren't optimised are the patterns the optimiser is missing.
+pub struct Profile {
+    // How many times the instruction at each index was run by itself
+    counts: Vec<u64>,
+    // Optimised block start -> (times run, total times round the loop)
+    blocks: BTreeMap<usize, (u64, u64)>
+}
+
+// A loop in the program: a backward jump from end to start
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct LoopRange {
+    start: usize,
+    end: usize
+}
+
+impl LoopRange {
+    fn contains(&self, pc: usize) -> bool {
+        pc >= self.start && pc <= self.end
+    }
+}
+
+// Every backward jump by a literal amount which can be taken, largest first
+fn loops(instructions: &[Instruction]) -> Vec<LoopRange> {
+    let mut loops:Vec<LoopRange> = instructions.iter().enumerate()
+        .filter_map(|(end, instr)| match *instr {
+            JumpNotZero{ check: Literal(0), .. } => None,
+            JumpNotZero{ delta: Literal(delta), .. } if delta < 0 && end as i64 + delta >= 0 => {
+                Some(LoopRange { start: (end as i64 + delta) as usize, end: end })
+            },
+            _ => None
+        })
+        .collect();
+    loops.sort_by_key(|l| (l.start as i64 - l.end as i64, l.start));
+    loops.dedup();
+    loops
+}
+
+fn percent(count: u64, total: u64) -> f64 {
+    if total == 0 { 0.0 } else { 100.0 * count as f64 / total as f64 }
+}
+
+impl Profile {
+    pub fn new(len: usize) -> Profile {
+        Profile {
+            counts: vec![0; len],
+            blocks: BTreeMap::new()
+        }
+    }
+
+    // Called with each instruction just before it runs
+    pub fn record(&mut self, pc: usize, instr: Instruction, regs: &[i64]) {
+        match instr {
+            Loop{ counter, .. } => {
+                let entry = self.blocks.entry(pc).or_insert((0, 0));
+                entry.0 += 1;
+                entry.1 += regs[counter].max(0) as u64;
+            },
+            _ => self.counts[pc] += 1
+        }
+    }
+
+    // How many times the instruction at pc ran by itself
+    pub fn count(&self, pc: usize) -> u64 {
+        self.counts[pc]
+    }
+
+    // How many times the optimised block at pc ran, and how many times round the loop that saved
+    pub fn block_runs(&self, pc: usize) -> Option<(u64, u64)> {
+        self.blocks.get(&pc).cloned()
+    }
+
+    // Everything the CPU ran, counting each optimised block as one
+    pub fn steps(&self) -> u64 {
+        self.counts.iter().sum::<u64>() + self.blocks.values().map(|&(runs, _)| runs).sum::<u64>()
+    }
+
+    // Runs at pc, whether by itself or as an optimised block
+    fn runs(&self, pc: usize) -> u64 {
+        self.counts[pc] + self.blocks.get(&pc).map_or(0, |&(runs, _)| runs)
+    }
+
+    // Prints the hottest instructions, the hottest loops and whether they were optimised, then every
+    // instruction run as a stack of the loops around it. The stacks are in the collapsed format
+    // flamegraph.pl reads, so `grep '^main' | flamegraph.pl` draws them.
+    //
+    // `instructions` is the program as written (with any toggles) and `optimised` is as it was run.
+    pub fn write_report<W: Write>(&self, instructions: &[Instruction], optimised: &[Instruction], out: &mut W) -> io::Result<()> {
+        let total = self.steps();
+        let saved:u64 = self.blocks.values().map(|&(_, iterations)| iterations).sum();
+        writeln!(out, "{} steps, {} of them optimised loops standing in for {} times round", total,
+                 self.blocks.values().map(|&(runs, _)| runs).sum::<u64>(), saved)?;
+
+        writeln!(out)?;
+        writeln!(out, "Hot spots")?;
+        writeln!(out, "{:>12} {:>6}  {:>4}  instruction", "count", "%", "pc")?;
+        let mut hot:Vec<usize> = (0..self.counts.len()).filter(|&pc| self.runs(pc) > 0).collect();
+        hot.sort_by_key(|&pc| (::std::cmp::Reverse(self.runs(pc)), pc));
+        for pc in hot.into_iter().take(10) {
+            writeln!(out, "{:>12} {:>5.1}%  {:>4}  {}", self.runs(pc), percent(self.runs(pc), total), pc, optimised[pc])?;
+        }
+
+        writeln!(out)?;
+        writeln!(out, "Hot loops")?;
+        writeln!(out, "{:>12} {:>6}  {:>9}", "count", "%", "lines")?;
+        let loops = loops(instructions);
+        let mut hot_loops:Vec<(u64, LoopRange)> = loops.iter()
+            .map(|&l| ((l.start..=l.end).map(|pc| self.runs(pc)).sum(), l))
+            .filter(|&(count, _)| count > 0)
+            .collect();
+        hot_loops.sort_by_key(|&(count, l)| (::std::cmp::Reverse(count), l.start));
+        for (count, l) in hot_loops {
+            let lines = format!("{}-{}", l.start, l.end);
+            let block = optimised.iter().enumerate()
+                .filter_map(|(start, instr)| match *instr {
+                    Loop{ len, .. } => Some(LoopRange { start: start, end: start + len - 1 }),
+                    _ => None
+                })
+                .find(|block| block.contains(l.start) && block.contains(l.end));
+            let state = match block {
+                Some(block) if block == l => format!("optimised to: {}", optimised[l.start]),
+                Some(block) => format!("inside the optimised loop at {}", block.start),
+                None => "not optimised".to_string()
+            };
+            writeln!(out, "{:>12} {:>5.1}%  {:>9}  {}", count, percent(count, total), lines, state)?;
+        }
+
+        writeln!(out)?;
+        writeln!(out, "Stacks")?;
+        for pc in (0..self.counts.len()).filter(|&pc| self.runs(pc) > 0) {
+            let mut frames = vec!["main".to_string()];
+            frames.extend(loops.iter().filter(|l| l.contains(pc)).map(|l| format!("loop {}-{}", l.start, l.end)));
+            // Semicolons separate the frames, so can't appear in an optimised loop's summary
+            frames.push(format!("{}: {}", pc, optimised[pc].to_string().replace(";", ",")));
+            writeln!(out, "{} {}", frames.join(";"), self.runs(pc))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+use ::cpu::Cpu;
+#[cfg(test)]
+use ::parser::parse;
+
+#[cfg(test)]
+static NESTED: &'static str = r"cpy 3 b
+cpy b c
+dec c
+inc a
+inc a
+inc a
+jnz c -4
+dec b
+jnz b -7";
+
+#[test]
+fn counts_are_kept_per_instruction_and_per_optimised_block() {
+    let mut cpu = Cpu::new(&[0, 0, 0, 0]);
+    cpu.set_profiled(true);
+    cpu.process(parse("cpy 4 d\ninc c\ndec d\njnz d -2\ninc a").unwrap()).unwrap();
+    let profile = cpu.profile().unwrap();
+    assert_eq!((profile.count(0), profile.count(1), profile.count(4)), (1, 0, 1));
+    assert_eq!(profile.block_runs(1), Some((1, 4)));
+    assert_eq!(profile.steps(), 3);
+}
+
+#[test]
+fn report_shows_hot_loops_the_optimiser_missed() {
+    // The inner loop adds 3 * c to a, but c is b, which goes down each time round the outer loop, so
+    // that adds 3 * b * (b + 1) / 2 which isn't linear
+    let instructions = parse(NESTED).unwrap();
+    let mut cpu = Cpu::new(&[0, 0, 0, 0]);
+    cpu.set_profiled(true);
+    cpu.process(instructions).unwrap();
+
+    let mut report = vec![];
+    cpu.profile().unwrap().write_report(cpu.unoptimised_instructions(), cpu.instructions(), &mut report).unwrap();
+    let report = String::from_utf8(report).unwrap();
+    assert!(report.starts_with("13 steps, 3 of them optimised loops standing in for 6 times round\n"), "Got:\n{}", report);
+    assert!(report.contains("           3  23.1%     1  cpy b c\n"), "Got:\n{}", report);
+    assert!(report.contains("          12  92.3%        1-8  not optimised\n"), "Got:\n{}", report);
+    assert!(report.contains("           3  23.1%        2-6  optimised to: a += 3 * c; c = 0\n"), "Got:\n{}", report);
+    assert!(report.contains("\nmain;loop 1-8;loop 2-6;2: a += 3 * c, c = 0 3\n"), "Got:\n{}", report);
+}
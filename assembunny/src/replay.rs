@@ -0,0 +1,286 @@
+use std::io::{self, BufRead, Write};
+
+use cpu::format_registers;
+use trace::{TraceStart, TraceStep};
+
+static HELP: &'static str = "Commands:
+  next [n]       (n)  replay n steps forwards (default 1)
+  back [n]       (b)  undo n steps (default 1)
+  goto <step>    (g)  jump to just before the given step
+  find <pc>      (f)  replay forwards until the program counter reaches pc
+  regs           (r)  show the registers
+  list           (l)  show the program, as toggled so far
+  quit           (q)";
+
+// Walks back and forth through a recorded trace. Each step records the before and after values of
+// whatever it changed, so it can be undone as easily as it can be redone.
+pub struct Replay {
+    program: Vec<String>,
+    steps: Vec<TraceStep>,
+    regs: Vec<i64>,
+    position: usize
+}
+
+fn register_index(name: &str) -> usize {
+    (name.as_bytes()[0] - b'a') as usize
+}
+
+impl Replay {
+    pub fn new(start: TraceStart, steps: Vec<TraceStep>) -> Replay {
+        Replay {
+            program: start.program,
+            steps: steps,
+            regs: start.registers,
+            position: 0
+        }
+    }
+
+    // The number of steps replayed so far, i.e. the index of the next step
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    pub fn regs(&self) -> &[i64] {
+        &self.regs
+    }
+
+    pub fn program(&self) -> &[String] {
+        &self.program
+    }
+
+    pub fn forward(&mut self) -> Option<&TraceStep> {
+        if self.position >= self.steps.len() {
+            return None;
+        }
+
+        let step = &self.steps[self.position];
+        for (name, &(_, after)) in &step.changes {
+            self.regs[register_index(name)] = after;
+        }
+        for toggle in &step.toggles {
+            self.program[toggle.pc as usize] = toggle.to.clone();
+        }
+        self.position += 1;
+
+        Some(step)
+    }
+
+    pub fn backward(&mut self) -> Option<&TraceStep> {
+        if self.position == 0 {
+            return None;
+        }
+
+        self.position -= 1;
+        let step = &self.steps[self.position];
+        for (name, &(before, _)) in &step.changes {
+            self.regs[register_index(name)] = before;
+        }
+        for toggle in &step.toggles {
+            self.program[toggle.pc as usize] = toggle.from.clone();
+        }
+
+        Some(step)
+    }
+
+    pub fn goto(&mut self, position: usize) {
+        while self.position < position && self.forward().is_some() {}
+        while self.position > position && self.backward().is_some() {}
+    }
+
+    // Reads commands until `quit` or the end of the input, in the same way as the debugger
+    pub fn run<R: BufRead, W: Write>(&mut self, commands: R, out: &mut W, interactive: bool) -> io::Result<()> {
+        self.show_position(out)?;
+
+        if interactive {
+            write!(out, "(replay) ")?;
+            out.flush()?;
+        }
+        for line in commands.lines() {
+            let line = line?;
+            if !interactive {
+                writeln!(out, "(replay) {}", line)?;
+            }
+            if !self.execute(line.trim(), out)? {
+                break;
+            }
+            if interactive {
+                write!(out, "(replay) ")?;
+                out.flush()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Runs a single command, returning false if it was asking to quit
+    pub fn execute<W: Write>(&mut self, command: &str, out: &mut W) -> io::Result<bool> {
+        let mut words = command.split_whitespace();
+        let name = match words.next() {
+            Some(name) => name,
+            None => return Ok(true)
+        };
+        let arg = words.next();
+        let count = arg.map_or(Ok(1), |c| c.parse::<usize>());
+
+        match (name, count) {
+            ("next", Ok(count)) | ("n", Ok(count)) => {
+                for _ in 0..count {
+                    match self.forward() {
+                        Some(step) => describe_step(step, out)?,
+                        None => break
+                    }
+                }
+                self.show_position(out)?;
+            },
+            ("back", Ok(count)) | ("b", Ok(count)) => {
+                for _ in 0..count {
+                    match self.backward() {
+                        Some(step) => writeln!(out, "undo {}: {}", step.step, step.instruction)?,
+                        None => break
+                    }
+                }
+                self.show_position(out)?;
+            },
+            ("goto", Ok(position)) | ("g", Ok(position)) if arg.is_some() => {
+                self.goto(position);
+                self.show_position(out)?;
+            },
+            ("find", Ok(pc)) | ("f", Ok(pc)) if arg.is_some() => {
+                let found = self.steps[self.position..].iter()
+                    .skip(1)
+                    .position(|step| step.pc == pc as i32)
+                    .map(|offset| self.position + offset + 1);
+                match found {
+                    Some(position) => self.goto(position),
+                    None => writeln!(out, "pc {} isn't reached again", pc)?
+                }
+                self.show_position(out)?;
+            },
+            ("regs", _) | ("r", _) => writeln!(out, "{}", format_registers(&self.regs))?,
+            ("list", _) | ("l", _) => {
+                let next_pc = self.steps.get(self.position).map(|step| step.pc as usize);
+                for (idx, instruction) in self.program.iter().enumerate() {
+                    let marker = if Some(idx) == next_pc { '>' } else { ' ' };
+                    writeln!(out, "{} {:>3}: {}", marker, idx, instruction)?;
+                }
+            },
+            ("help", _) | ("h", _) => writeln!(out, "{}", HELP)?,
+            ("quit", _) | ("q", _) => return Ok(false),
+            _ => writeln!(out, "Didn't understand '{}', try 'help'", command)?
+        }
+
+        Ok(true)
+    }
+
+    fn show_position<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        match self.steps.get(self.position) {
+            Some(step) => writeln!(out, "Step {} of {}, next {}: {}", self.position, self.steps.len(), step.pc, step.instruction)?,
+            None => writeln!(out, "End of trace ({} steps)", self.steps.len())?
+        }
+        writeln!(out, "{}", format_registers(&self.regs))
+    }
+}
+
+fn describe_step<W: Write>(step: &TraceStep, out: &mut W) -> io::Result<()> {
+    let changes:Vec<String> = step.changes.iter()
+        .map(|(name, &(before, after))| format!("{}: {} -> {}", name, before, after))
+        .collect();
+    writeln!(out, "{} @ {}: {}  {}", step.step, step.pc, step.instruction, changes.join(", "))?;
+    for toggle in &step.toggles {
+        writeln!(out, "    tgl changed {}: '{}' -> '{}'", toggle.pc, toggle.from, toggle.to)?;
+    }
+    if let Some(val) = step.output {
+        writeln!(out, "    out: {}", val)?;
+    }
+    if let Some(val) = step.input {
+        writeln!(out, "    in: {}", val)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+use std::cell::RefCell;
+#[cfg(test)]
+use std::rc::Rc;
+
+// Lets a test hold on to what the CPU writes into its boxed trace writer
+#[cfg(test)]
+#[derive(Clone)]
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+#[cfg(test)]
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+fn record_trace(program: &str, regs: &[i64], optimised: bool) -> (Replay, Vec<i64>) {
+    let buffer = SharedBuffer(Rc::new(RefCell::new(vec![])));
+    let mut cpu = ::cpu::Cpu::new(regs);
+    cpu.set_optimised(optimised);
+    cpu.trace_to(Box::new(buffer.clone()));
+    cpu.process(::parser::parse(program).unwrap()).unwrap();
+    cpu.finish_trace().unwrap();
+
+    let trace = buffer.0.borrow();
+    let (start, steps) = ::trace::read_trace(&trace[..]).unwrap();
+    (Replay::new(start, steps), cpu.regs)
+}
+
+#[cfg(test)]
+static TOGGLING_PROGRAM: &'static str = r"cpy 2 a
+tgl a
+tgl a
+tgl a
+cpy 1 a
+dec a
+dec a";
+
+#[test]
+fn replaying_forwards_reaches_final_registers_and_backwards_returns_to_start() {
+    let (mut replay, final_regs) = record_trace(TOGGLING_PROGRAM, &[0, 0, 0, 0], true);
+    let original_program = replay.program().to_vec();
+
+    replay.goto(replay.len());
+    assert_eq!(replay.regs(), &final_regs[..]);
+    assert_eq!(replay.program()[3], "inc a");
+    assert_eq!(replay.program()[4], "jnz 1 a");
+
+    replay.goto(0);
+    assert_eq!(replay.regs(), [0, 0, 0, 0]);
+    assert_eq!(replay.program(), &original_program[..]);
+}
+
+#[test]
+fn optimised_and_raw_traces_end_in_the_same_state() {
+    let program = r"cpy 3 b
+cpy 4 d
+cpy b c
+inc a
+dec c
+jnz c -2
+dec d
+jnz d -5";
+    let (mut optimised, _) = record_trace(program, &[0, 0, 0, 0], true);
+    let (mut raw, _) = record_trace(program, &[0, 0, 0, 0], false);
+    assert!(optimised.len() < raw.len());
+
+    optimised.goto(optimised.len());
+    raw.goto(raw.len());
+    assert_eq!(optimised.regs(), [12, 3, 0, 0]);
+    assert_eq!(optimised.regs(), raw.regs());
+}
@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, Write};
+
+use serde_json;
+
+use parse_error::ParseError;
+
+// A trace is written as JSON lines: one `start` record with the registers and program, followed by
+// a `step` record for every instruction executed. Instructions are recorded as text, so a trace
+// can be read without the program it came from.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TraceEvent {
+    Start(TraceStart),
+    Step(TraceStep)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TraceStart {
+    pub registers: Vec<i64>,
+    pub program: Vec<String>,
+    pub optimised: bool
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TraceStep {
+    pub step: usize,
+    pub pc: i32,
+    pub instruction: String,
+    // Register name -> (before, after), for the registers which changed
+    pub changes: BTreeMap<String, (i64, i64)>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub toggles: Vec<Toggled>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input: Option<i64>
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Toggled {
+    pub pc: i32,
+    pub from: String,
+    pub to: String
+}
+
+// Writes trace events as they happen. The CPU can't stop to report an I/O error in the middle of
+// a step, so the first one is kept and returned by finish.
+pub struct Tracer {
+    out: Box<dyn Write>,
+    steps: usize,
+    error: Option<io::Error>
+}
+
+impl Tracer {
+    pub fn new(out: Box<dyn Write>) -> Tracer {
+        Tracer {
+            out: out,
+            steps: 0,
+            error: None
+        }
+    }
+
+    pub fn next_step(&mut self) -> usize {
+        self.steps += 1;
+        self.steps - 1
+    }
+
+    pub fn record(&mut self, event: &TraceEvent) {
+        if self.error.is_some() {
+            return;
+        }
+
+        let result = serde_json::to_writer(&mut self.out, event)
+            .map_err(io::Error::from)
+            .and_then(|_| writeln!(self.out));
+        if let Err(e) = result {
+            self.error = Some(e);
+        }
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        match self.error {
+            Some(e) => Err(e),
+            None => self.out.flush()
+        }
+    }
+}
+
+// Checks a step only changes registers the trace started with and only toggles instructions in the
+// program, as a trace edited by hand or cut short could say anything
+fn check_step(step: &TraceStep, start: &TraceStart, line_num: usize) -> Result<(), ParseError> {
+    for name in step.changes.keys() {
+        let bytes = name.as_bytes();
+        if bytes.len() != 1 || bytes[0] < b'a' || (bytes[0] - b'a') as usize >= start.registers.len() {
+            return Err(ParseError::new(line_num, 1, name, "Step changes a register the trace doesn't start with"));
+        }
+    }
+    for toggle in &step.toggles {
+        if toggle.pc < 0 || toggle.pc as usize >= start.program.len() {
+            return Err(ParseError::new(line_num, 1, &toggle.pc.to_string(), "Step toggles an instruction outside the program"));
+        }
+    }
+    Ok(())
+}
+
+// Reads a whole trace back in, checking it starts with a `start` record and that every step fits
+// the registers and program it starts with
+pub fn read_trace<R: BufRead>(trace: R) -> Result<(TraceStart, Vec<TraceStep>), ParseError> {
+    let mut start = None;
+    let mut steps = vec![];
+
+    for (i, line) in trace.lines().enumerate() {
+        let line = line.map_err(|e| ParseError::new(i + 1, 1, "", &e.to_string()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let event = serde_json::from_str::<TraceEvent>(&line)
+            .map_err(|e| ParseError::new(i + 1, e.column(), &line, &e.to_string()))?;
+        match (event, start.as_ref()) {
+            (TraceEvent::Start(s), None) => start = Some(s),
+            (TraceEvent::Step(s), Some(trace_start)) => {
+                check_step(&s, trace_start, i + 1)?;
+                steps.push(s);
+            },
+            (TraceEvent::Start(_), Some(_)) => return Err(ParseError::new(i + 1, 1, &line, "Trace has more than one start record")),
+            (TraceEvent::Step(_), None) => return Err(ParseError::new(i + 1, 1, &line, "Trace must begin with a start record"))
+        }
+    }
+
+    match start {
+        Some(start) => Ok((start, steps)),
+        None => Err(ParseError::new(1, 1, "", "Trace is empty"))
+    }
+}
+
+#[test]
+fn steps_outside_the_registers_or_program_are_rejected() {
+    let start = r#"{"type":"start","registers":[0,0],"program":["inc a","tgl a"],"optimised":false}"#;
+    let step = |changes: &str, toggles: &str| {
+        format!(r#"{}
+{{"type":"step","step":0,"pc":1,"instruction":"tgl a","changes":{},"toggles":{}}}"#, start, changes, toggles)
+    };
+
+    assert!(read_trace(step(r#"{"b":[0,1]}"#, r#"[{"pc":1,"from":"tgl a","to":"inc a"}]"#).as_bytes()).is_ok());
+    assert_eq!(read_trace(step(r#"{"c":[0,1]}"#, "[]").as_bytes()),
+               Err(ParseError::new(2, 1, "c", "Step changes a register the trace doesn't start with")));
+    assert_eq!(read_trace(step(r#"{"":[0,1]}"#, "[]").as_bytes()),
+               Err(ParseError::new(2, 1, "", "Step changes a register the trace doesn't start with")));
+    assert_eq!(read_trace(step("{}", r#"[{"pc":2,"from":"tgl a","to":"inc a"}]"#).as_bytes()),
+               Err(ParseError::new(2, 1, "2", "Step toggles an instruction outside the program")));
+}
@@ -0,0 +1,131 @@
+extern crate parse_error;
+extern crate regex;
+
+use std::error::Error;
+use std::fmt;
+
+use parse_error::ParseError;
+use regex::Regex;
+
+mod segments;
+mod walk;
+pub use segments::{Segment, first_revisit, segments};
+pub use walk::{CompassDir, TurnDir, Walk, distance};
+
+// Why solve couldn't give both answers
+#[derive(Debug, PartialEq)]
+pub enum SolveError {
+    Parse(ParseError),
+    // The route never crosses itself, so there's no answer to part 2
+    NoRevisit
+}
+
+impl From<ParseError> for SolveError {
+    fn from(error: ParseError) -> SolveError {
+        SolveError::Parse(error)
+    }
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SolveError::Parse(ref error) => write!(f, "{}", error),
+            SolveError::NoRevisit => write!(f, "No position was visited twice")
+        }
+    }
+}
+
+impl Error for SolveError {}
+
+// Each move as a turn then how many blocks to go
+fn parse(instr_str: &str) -> Result<Vec<(TurnDir, i64)>, ParseError> {
+    let re:Regex = Regex::new(r"^(?P<dir>[RL])(?P<dist>\d+)$").unwrap();
+
+    let mut moves = vec![];
+    for (i, line) in instr_str.trim().lines().enumerate() {
+        let line = line.trim_end();
+        for instr in line.split(", ") {
+            let captures = match re.captures(instr) {
+                Some(captures) => captures,
+                None => return Err(ParseError::at(i + 1, line, instr, "Expected a turn and a number of blocks, like R8"))
+            };
+            let turn = match captures.name("dir").unwrap() {
+                "R" => TurnDir::Right,
+                _ => TurnDir::Left
+            };
+            // The regex only matches digits, but the number could still be too big
+            let blocks = captures.name("dist").unwrap();
+            let blocks = blocks.parse::<i64>().map_err(|_| ParseError::at(i + 1, line, blocks, "Number is too large"))?;
+            moves.push((turn, blocks));
+        }
+    }
+    Ok(moves)
+}
+
+// Follows the instructions a block at a time
+pub fn walk(instr_str: &str) -> Result<Walk, ParseError> {
+    let mut walk = Walk::new();
+    for (turn, blocks) in parse(instr_str)? {
+        walk.turn(turn);
+        walk.forward(blocks);
+    }
+    Ok(walk)
+}
+
+// Returns the distance to the final position, and the distance to the first position visited twice,
+// going by whole moves so long moves don't take any longer
+pub fn solve_by_segments(instr_str: &str) -> Result<(i64, i64), SolveError> {
+    let segments = segments(&parse(instr_str)?);
+    let end = segments.last().map_or((0, 0), |s| s.end());
+    let first_repeat = first_revisit(&segments).ok_or(SolveError::NoRevisit)?;
+    Ok((end.0.abs() + end.1.abs(), first_repeat.0.abs() + first_repeat.1.abs()))
+}
+
+// Returns the distance to the final position, and the distance to the first position visited twice
+pub fn solve(instr_str: &str) -> Result<(i64, i64), SolveError> {
+    solve_by_segments(instr_str)
+}
+
+// The same as solve, but walking every block; kept to check the segments against
+pub fn solve_by_steps(instr_str: &str) -> Result<(i64, i64), SolveError> {
+    let walk = walk(instr_str)?;
+    let first_repeat = *walk.intersections().first().ok_or(SolveError::NoRevisit)?;
+    Ok((distance(walk.position()), distance(first_repeat)))
+}
+
+#[test]
+fn r8_r4_r4_r8_first_revisits_4_blocks_away() {
+    assert_eq!(solve("R8, R4, R4, R8").unwrap().1, 4);
+}
+
+#[test]
+fn solves_puzzle_input() {
+    assert_eq!(solve(include_str!("input.txt")), Ok((241, 116)));
+}
+
+#[test]
+fn segments_and_steps_agree_on_puzzle_input() {
+    assert_eq!(solve(include_str!("input.txt")), solve_by_steps(include_str!("input.txt")));
+}
+
+#[test]
+fn moves_of_millions_of_blocks_are_solved_by_segments() {
+    // Scaling every move up scales the whole route, crossings included
+    let scaled:Vec<String> = include_str!("input.txt").trim().split(", ")
+        .map(|i| format!("{}{}000000", &i[..1], &i[1..]))
+        .collect();
+    assert_eq!(solve_by_segments(&scaled.join(", ")), Ok((241000000, 116000000)));
+}
+
+#[test]
+fn route_which_never_crosses_itself_has_no_revisit() {
+    assert_eq!(solve("R2, L3"), Err(SolveError::NoRevisit));
+    assert_eq!(solve_by_steps("R2, L3"), Err(SolveError::NoRevisit));
+}
+
+#[test]
+fn bad_moves_are_reported_with_their_column() {
+    assert_eq!(solve("R2, X3, L1"), Err(SolveError::Parse(ParseError::new(1, 5, "X3", "Expected a turn and a number of blocks, like R8"))));
+    assert_eq!(solve("R2\nL99999999999999999999"),
+               Err(SolveError::Parse(ParseError::new(2, 2, "99999999999999999999", "Number is too large"))));
+}
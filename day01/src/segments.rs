@@ -0,0 +1,99 @@
+use ::walk::{CompassDir, TurnDir};
+
+// A straight move along the grid: the blocks start + t * delta for t in 0..=len
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Segment {
+    pub start: (i64, i64),
+    pub delta: (i64, i64),
+    pub len: i64
+}
+
+impl Segment {
+    pub fn end(&self) -> (i64, i64) {
+        (self.start.0 + self.len * self.delta.0, self.start.1 + self.len * self.delta.1)
+    }
+
+    fn at(&self, t: i64) -> (i64, i64) {
+        (self.start.0 + t * self.delta.0, self.start.1 + t * self.delta.1)
+    }
+
+    // How far along this segment's direction a block is, and how far off to the side of it
+    fn along(&self, pos: (i64, i64)) -> i64 {
+        pos.0 * self.delta.0 + pos.1 * self.delta.1
+    }
+
+    fn across(&self, pos: (i64, i64)) -> i64 {
+        pos.0 * self.delta.1 - pos.1 * self.delta.0
+    }
+
+    // The first block of this segment after its start which is also on `other`, as how far along
+    // this segment it is
+    fn first_meeting(&self, other: &Segment) -> Option<i64> {
+        let side = self.across(self.start);
+        let (first, last) = if self.across(other.delta) == 0 {
+            // Parallel, so they either share a stretch of the same line or never meet
+            if self.across(other.start) != side {
+                return None;
+            }
+            let from = self.along(other.start) - self.along(self.start);
+            let to = self.along(other.end()) - self.along(self.start);
+            (from.min(to), from.max(to))
+        } else {
+            // Crossing at right angles, where other reaches this segment's line
+            let u = (side - self.across(other.start)) * self.across(other.delta);
+            if u < 0 || u > other.len {
+                return None;
+            }
+            let t = self.along(other.at(u)) - self.along(self.start);
+            (t, t)
+        };
+
+        let first = first.max(1);
+        if first <= last.min(self.len) { Some(first) } else { None }
+    }
+}
+
+// Turns the moves into segments, starting at (0, 0) facing north
+pub fn segments(moves: &[(TurnDir, i64)]) -> Vec<Segment> {
+    let mut facing = CompassDir::North;
+    let mut pos = (0, 0);
+    moves.iter().map(|&(turn, len)| {
+        facing = facing.turn(turn);
+        let segment = Segment { start: pos, delta: facing.delta(), len: len };
+        pos = segment.end();
+        segment
+    }).collect()
+}
+
+// The first block visited twice, checking each segment against every one before it, so this takes
+// time by the number of moves squared rather than by how far they go
+pub fn first_revisit(segments: &[Segment]) -> Option<(i64, i64)> {
+    for (i, segment) in segments.iter().enumerate() {
+        // The segment just before only meets this one at its start, which doesn't count
+        let first = segments[..i].iter().filter_map(|earlier| segment.first_meeting(earlier)).min();
+        if let Some(t) = first {
+            return Some(segment.at(t));
+        }
+    }
+    None
+}
+
+#[test]
+fn crossing_at_right_angles_is_found() {
+    // R8, R4, R4, R8 crosses the first move at (4, 0)
+    let moves = [(TurnDir::Right, 8), (TurnDir::Right, 4), (TurnDir::Right, 4), (TurnDir::Right, 8)];
+    assert_eq!(first_revisit(&segments(&moves)), Some((4, 0)));
+}
+
+#[test]
+fn going_back_along_an_earlier_move_is_found_at_its_first_block() {
+    // R2, L1, R2, R1, R5 comes back west along the line of the first move, reaching its end at (2, 0)
+    let moves = [(TurnDir::Right, 2), (TurnDir::Left, 1), (TurnDir::Right, 2), (TurnDir::Right, 1), (TurnDir::Right, 5)];
+    assert_eq!(first_revisit(&segments(&moves)), Some((2, 0)));
+}
+
+#[test]
+fn walk_that_never_crosses_itself_has_no_revisit() {
+    let moves = [(TurnDir::Right, 5), (TurnDir::Left, 5), (TurnDir::Left, 3), (TurnDir::Left, 2)];
+    assert_eq!(first_revisit(&segments(&moves)), None);
+}
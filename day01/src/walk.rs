@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TurnDir { Right, Left }
+
+#[derive(Hash, Eq, PartialEq, Copy, Clone, Debug)]
+pub enum CompassDir {
+    North,
+    East,
+    South,
+    West
+}
+
+impl CompassDir {
+    pub fn turn(&self, turn: TurnDir) -> CompassDir {
+        match (*self, turn) {
+            (CompassDir::North, TurnDir::Left) => CompassDir::West,
+            (CompassDir::North, TurnDir::Right) => CompassDir::East,
+            (CompassDir::East, TurnDir::Left) => CompassDir::North,
+            (CompassDir::East, TurnDir::Right) => CompassDir::South,
+            (CompassDir::South, TurnDir::Left) => CompassDir::East,
+            (CompassDir::South, TurnDir::Right) => CompassDir::West,
+            (CompassDir::West, TurnDir::Left) => CompassDir::South,
+            (CompassDir::West, TurnDir::Right) => CompassDir::North
+        }
+    }
+
+    // One block in this direction, with north as +y
+    pub fn delta(&self) -> (i64, i64) {
+        match *self {
+            CompassDir::North => (0, 1),
+            CompassDir::East => (1, 0),
+            CompassDir::South => (0, -1),
+            CompassDir::West => (-1, 0)
+        }
+    }
+}
+
+// Blocks away from the start, going along the grid
+pub fn distance(position: (i64, i64)) -> i64 {
+    position.0.abs() + position.1.abs()
+}
+
+// A walk around the city grid a block at a time, starting at (0, 0) facing north, remembering every
+// block it passes through
+pub struct Walk {
+    facing: CompassDir,
+    path: Vec<(i64, i64)>,
+    visits: HashMap<(i64, i64), usize>,
+    intersections: Vec<(i64, i64)>
+}
+
+impl Walk {
+    pub fn new() -> Walk {
+        let mut visits = HashMap::new();
+        visits.insert((0, 0), 1);
+        Walk {
+            facing: CompassDir::North,
+            path: vec![(0, 0)],
+            visits: visits,
+            intersections: vec![]
+        }
+    }
+
+    pub fn turn(&mut self, turn: TurnDir) {
+        self.facing = self.facing.turn(turn);
+    }
+
+    pub fn forward(&mut self, blocks: i64) {
+        let (x_delta, y_delta) = self.facing.delta();
+        for _ in 0..blocks {
+            let (x, y) = self.position();
+            let next = (x + x_delta, y + y_delta);
+
+            let visits = self.visits.entry(next).or_insert(0);
+            *visits += 1;
+            if *visits > 1 {
+                self.intersections.push(next);
+            }
+            self.path.push(next);
+        }
+    }
+
+    pub fn position(&self) -> (i64, i64) {
+        self.path[self.path.len() - 1]
+    }
+
+    // Every block passed through, in order, starting with (0, 0)
+    pub fn path(&self) -> &[(i64, i64)] {
+        &self.path
+    }
+
+    // Every time the walk came back to a block it had already been through, in order. A block gone
+    // through three times shows up twice.
+    pub fn intersections(&self) -> &[(i64, i64)] {
+        &self.intersections
+    }
+
+    pub fn visits(&self, position: (i64, i64)) -> usize {
+        self.visits.get(&position).cloned().unwrap_or(0)
+    }
+
+    // (min x, min y, max x, max y) of everywhere the walk has been
+    fn bounds(&self) -> (i64, i64, i64, i64) {
+        self.path.iter().fold((0, 0, 0, 0), |(min_x, min_y, max_x, max_y), &(x, y)| {
+            (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+        })
+    }
+
+    // Draws the walk with north at the top: S is the start, E the end, # a block passed through once
+    // and X one passed through more than once
+    pub fn to_ascii(&self) -> String {
+        let (min_x, min_y, max_x, max_y) = self.bounds();
+        let end = self.position();
+
+        let mut drawing = String::new();
+        for y in (min_y..=max_y).rev() {
+            for x in min_x..=max_x {
+                drawing.push(match self.visits((x, y)) {
+                    _ if (x, y) == (0, 0) => 'S',
+                    _ if (x, y) == end => 'E',
+                    0 => '.',
+                    1 => '#',
+                    _ => 'X'
+                });
+            }
+            drawing.push('\n');
+        }
+        drawing
+    }
+
+    // Draws the walk as an SVG line, with a green start, a blue end and a red circle wherever it
+    // crosses itself. SVG's y goes down, so it's flipped to keep north at the top.
+    pub fn write_svg<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let (min_x, min_y, max_x, max_y) = self.bounds();
+        writeln!(out, "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">",
+                 min_x - 1, -max_y - 1, max_x - min_x + 2, max_y - min_y + 2)?;
+
+        let points:Vec<String> = self.path.iter().map(|&(x, y)| format!("{},{}", x, -y)).collect();
+        writeln!(out, "  <polyline points=\"{}\" fill=\"none\" stroke=\"black\" stroke-width=\"0.2\"/>", points.join(" "))?;
+
+        let mut crossings = self.intersections.clone();
+        crossings.sort();
+        crossings.dedup();
+        for (x, y) in crossings {
+            writeln!(out, "  <circle cx=\"{}\" cy=\"{}\" r=\"0.4\" fill=\"red\"/>", x, -y)?;
+        }
+        let (end_x, end_y) = self.position();
+        writeln!(out, "  <circle cx=\"0\" cy=\"0\" r=\"0.5\" fill=\"green\"/>")?;
+        writeln!(out, "  <circle cx=\"{}\" cy=\"{}\" r=\"0.5\" fill=\"blue\"/>", end_x, -end_y)?;
+        writeln!(out, "</svg>")
+    }
+}
+
+impl Default for Walk {
+    fn default() -> Walk {
+        Walk::new()
+    }
+}
+
+#[cfg(test)]
+fn square_with_a_tail() -> Walk {
+    // R4, R4, R4, R6, R2, R4
+    let mut walk = Walk::new();
+    for &blocks in &[4, 4, 4, 6, 2, 4] {
+        walk.turn(TurnDir::Right);
+        walk.forward(blocks);
+    }
+    walk
+}
+
+#[test]
+fn every_intersection_is_reported() {
+    let walk = square_with_a_tail();
+    assert_eq!(walk.position(), (2, -2));
+    assert_eq!(walk.intersections(), &[(0, 0), (2, 0)]);
+    assert_eq!(walk.visits((0, 0)), 2);
+    assert_eq!(walk.path().len(), 25);
+}
+
+#[test]
+fn ascii_drawing_marks_start_end_and_crossings() {
+    assert_eq!(square_with_a_tail().to_ascii(), "\
+###..
+#.#..
+S#X##
+#.#.#
+#.E.#
+#...#
+#####
+");
+}
+
+#[test]
+fn svg_has_the_whole_path_and_its_crossings() {
+    let mut svg = vec![];
+    square_with_a_tail().write_svg(&mut svg).unwrap();
+    let svg = String::from_utf8(svg).unwrap();
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"-1 -3 6 8\">\n"), "Got:\n{}", svg);
+    assert!(svg.contains("points=\"0,0 1,0 2,0 3,0 4,0 4,1 4,2"), "Got:\n{}", svg);
+    assert!(svg.contains("<circle cx=\"2\" cy=\"0\" r=\"0.4\" fill=\"red\"/>"), "Got:\n{}", svg);
+}
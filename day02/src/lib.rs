@@ -0,0 +1,122 @@
+// Which ways a keypad's finger can move. Eight adds the diagonals, written Q (up and left), E (up and
+// right), Z (down and left) and C (down and right) after the keys around S on a keyboard.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Moves { Four, Eight }
+
+// A keypad laid out as a text grid, one character per key, with spaces where there isn't a key
+pub struct GridKeypad {
+    keys: Vec<Vec<Option<char>>>,
+    start: (usize, usize),
+    pos: (usize, usize),
+    moves: Moves
+}
+
+impl GridKeypad {
+    pub fn parse(layout: &str, start: char, moves: Moves) -> Result<GridKeypad, String> {
+        let keys:Vec<Vec<Option<char>>> = layout.lines()
+            .map(|line| line.chars().map(|c| if c == ' ' { None } else { Some(c) }).collect())
+            .collect();
+        let start_pos = keys.iter().enumerate()
+            .filter_map(|(row, line)| line.iter().position(|&key| key == Some(start)).map(|col| (row, col)))
+            .next()
+            .ok_or(format!("There's no {} key to start on", start))?;
+
+        Ok(GridKeypad {
+            keys: keys,
+            start: start_pos,
+            pos: start_pos,
+            moves: moves
+        })
+    }
+
+    pub fn reset(&mut self) {
+        self.pos = self.start;
+    }
+
+    pub fn get_key(&self) -> char {
+        self.key_at(self.pos.0 as i32, self.pos.1 as i32).unwrap()
+    }
+
+    fn key_at(&self, row: i32, col: i32) -> Option<char> {
+        if row < 0 || col < 0 {
+            return None;
+        }
+        self.keys.get(row as usize).and_then(|line| line.get(col as usize)).cloned().unwrap_or(None)
+    }
+
+    // Moves by the given rows and columns, unless there's no key there
+    pub fn step(&mut self, row_delta: i32, col_delta: i32) {
+        let row = self.pos.0 as i32 + row_delta;
+        let col = self.pos.1 as i32 + col_delta;
+        if self.key_at(row, col).is_some() {
+            self.pos = (row as usize, col as usize);
+        }
+    }
+
+    // Follows one instruction, ignoring anything that isn't a move this keypad can make
+    pub fn follow(&mut self, instruction: char) {
+        let (row_delta, col_delta) = match (instruction, self.moves) {
+            ('U', _) => (-1, 0),
+            ('D', _) => (1, 0),
+            ('L', _) => (0, -1),
+            ('R', _) => (0, 1),
+            ('Q', Moves::Eight) => (-1, -1),
+            ('E', Moves::Eight) => (-1, 1),
+            ('Z', Moves::Eight) => (1, -1),
+            ('C', Moves::Eight) => (1, 1),
+            _ => return
+        };
+        self.step(row_delta, col_delta);
+    }
+}
+
+static SIMPLE_LAYOUT: &'static str = "\
+123
+456
+789";
+
+static COMPLEX_LAYOUT: &'static str = "  1
+ 234
+56789
+ ABC
+  D";
+
+pub fn solve(code: &str) -> (String, String) {
+    let mut simple = GridKeypad::parse(SIMPLE_LAYOUT, '5', Moves::Four).unwrap();
+    let mut complex = GridKeypad::parse(COMPLEX_LAYOUT, '5', Moves::Four).unwrap();
+    (get_answer(code, &mut simple), get_answer(code, &mut complex))
+}
+
+pub fn get_answer(code: &str, keypad: &mut GridKeypad) -> String {
+    let mut answer = String::new();
+
+    for line in code.lines() {
+        for char in line.chars() {
+            keypad.follow(char);
+        }
+
+        answer.push(keypad.get_key());
+        keypad.reset();
+    }
+
+    answer
+}
+
+#[test]
+fn solves_puzzle_input() {
+    assert_eq!(solve(include_str!("input.txt")), ("33444".to_string(), "446A6".to_string()));
+}
+
+#[test]
+fn diagonals_are_only_followed_on_eight_way_keypads() {
+    let code = "E\nZZ\nCU";
+    assert_eq!(get_answer(code, &mut GridKeypad::parse(SIMPLE_LAYOUT, '5', Moves::Eight).unwrap()), "376");
+    assert_eq!(get_answer(code, &mut GridKeypad::parse(SIMPLE_LAYOUT, '5', Moves::Four).unwrap()), "552");
+}
+
+#[test]
+fn gaps_in_a_layout_cant_be_moved_onto() {
+    let mut keypad = GridKeypad::parse("1 2\n 3\n4 5", '3', Moves::Eight).unwrap();
+    assert_eq!(get_answer("Q\nU\nCR\nLLLL", &mut keypad), "1353");
+    assert!(GridKeypad::parse(SIMPLE_LAYOUT, '0', Moves::Four).is_err());
+}
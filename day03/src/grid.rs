@@ -0,0 +1,138 @@
+use std::io::BufRead;
+
+use parse_error::ParseError;
+
+// Whether each item is a row of the grid, or a column out of the next N rows
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Order { Rows, Columns }
+
+// Reads a grid of whitespace separated numbers, N to a line, a line at a time so the input never
+// has to fit in memory. Blank lines are skipped. In column order each N rows are read together and
+// handed out as their N columns, so
+//   1 2
+//   3 4
+// gives [1, 3] then [2, 4].
+pub struct GridReader<R, const N: usize> {
+    input: R,
+    order: Order,
+    line_num: usize,
+    // Columns of the last N rows still to hand out, last one first
+    columns: Vec<[u32; N]>
+}
+
+impl<R: BufRead, const N: usize> GridReader<R, N> {
+    pub fn new(input: R, order: Order) -> GridReader<R, N> {
+        GridReader {
+            input: input,
+            order: order,
+            line_num: 0,
+            columns: vec![]
+        }
+    }
+
+    fn next_row(&mut self) -> Option<Result<[u32; N], ParseError>> {
+        loop {
+            let mut line = String::new();
+            match self.input.read_line(&mut line) {
+                Ok(0) => return None,
+                Ok(_) => {},
+                Err(e) => return Some(Err(ParseError::new(self.line_num + 1, 1, "", &format!("Couldn't read the line ({})", e))))
+            }
+            self.line_num += 1;
+            if !line.trim().is_empty() {
+                return Some(parse_row(self.line_num, &line));
+            }
+        }
+    }
+
+    fn next_columns(&mut self) -> Option<Result<[u32; N], ParseError>> {
+        if let Some(column) = self.columns.pop() {
+            return Some(Ok(column));
+        }
+
+        let mut rows = vec![];
+        while rows.len() < N {
+            match self.next_row() {
+                Some(Ok(row)) => rows.push(row),
+                Some(Err(e)) => return Some(Err(e)),
+                None if rows.is_empty() => return None,
+                None => return Some(Err(ParseError::new(self.line_num + 1, 1, "",
+                    &format!("Expected {} rows to read as columns, but there were only {}", N, rows.len()))))
+            }
+        }
+
+        self.columns = (0..N).rev().map(|col| {
+            let mut column = [0; N];
+            for (cell, row) in column.iter_mut().zip(rows.iter()) {
+                *cell = row[col];
+            }
+            column
+        }).collect();
+        self.columns.pop().map(Ok)
+    }
+}
+
+impl<R: BufRead, const N: usize> Iterator for GridReader<R, N> {
+    type Item = Result<[u32; N], ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.order {
+            Order::Rows => self.next_row(),
+            Order::Columns => self.next_columns()
+        }
+    }
+}
+
+fn parse_row<const N: usize>(line_num: usize, line: &str) -> Result<[u32; N], ParseError> {
+    let mut row = [0; N];
+    let mut tokens = line.split_whitespace();
+    for (found, cell) in row.iter_mut().enumerate() {
+        let token = match tokens.next() {
+            Some(token) => token,
+            None => {
+                let end = line.trim_end().chars().count() + 1;
+                return Err(ParseError::new(line_num, end, "", &format!("Expected {} numbers but there were only {}", N, found)));
+            }
+        };
+        *cell = token.parse().map_err(|_| ParseError::at(line_num, line, token, "Expected a number"))?;
+    }
+    if let Some(extra) = tokens.next() {
+        return Err(ParseError::at(line_num, line, extra, &format!("Expected only {} numbers", N)));
+    }
+    Ok(row)
+}
+
+#[cfg(test)]
+static SIX_BY_TWO: &'static str = "  1  2
+  3  4
+
+  5  6
+  7  8
+  9 10
+ 11 12
+";
+
+#[test]
+fn rows_and_columns_come_from_the_same_grid() {
+    let rows:Vec<[u32; 2]> = GridReader::new(SIX_BY_TWO.as_bytes(), Order::Rows).map(Result::unwrap).collect();
+    assert_eq!(rows, vec![[1, 2], [3, 4], [5, 6], [7, 8], [9, 10], [11, 12]]);
+
+    let columns:Vec<[u32; 2]> = GridReader::new(SIX_BY_TWO.as_bytes(), Order::Columns).map(Result::unwrap).collect();
+    assert_eq!(columns, vec![[1, 3], [2, 4], [5, 7], [6, 8], [9, 11], [10, 12]]);
+
+    let columns:Vec<[u32; 3]> = GridReader::new("1 2 3\n4 5 6\n7 8 9".as_bytes(), Order::Columns).map(Result::unwrap).collect();
+    assert_eq!(columns, vec![[1, 4, 7], [2, 5, 8], [3, 6, 9]]);
+}
+
+#[test]
+fn bad_tokens_are_reported_with_their_line_and_column() {
+    let mut reader:GridReader<_, 3> = GridReader::new("1 2 3\n4 x 6\n7 8\n1 2 3 4".as_bytes(), Order::Rows);
+    assert!(reader.next().unwrap().is_ok());
+    assert_eq!(reader.next().unwrap(), Err(ParseError::new(2, 3, "x", "Expected a number")));
+    assert_eq!(reader.next().unwrap(), Err(ParseError::new(3, 4, "", "Expected 3 numbers but there were only 2")));
+    assert_eq!(reader.next().unwrap(), Err(ParseError::new(4, 7, "4", "Expected only 3 numbers")));
+    assert!(reader.next().is_none());
+
+    let mut reader:GridReader<_, 3> = GridReader::new("1 2 3\n4 5 6\n".as_bytes(), Order::Columns);
+    assert_eq!(reader.next().unwrap(), Err(ParseError::new(3, 1, "", "Expected 3 rows to read as columns, but there were only 2")));
+}
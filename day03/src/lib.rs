@@ -0,0 +1,164 @@
+extern crate parse_error;
+extern crate regex;
+
+mod grid;
+
+use std::fmt;
+use std::io::BufRead;
+
+use parse_error::ParseError;
+pub use grid::{GridReader, Order};
+
+#[derive(Copy, Clone)]
+struct Triangle {
+    a: u32,
+    b: u32,
+    c: u32
+}
+
+impl Triangle {
+    fn new(a: u32, b: u32, c: u32) -> Triangle {
+        Triangle { a: a, b: b, c: c }
+    }
+
+    // Sides are added as u64s so that huge sides can't wrap round
+    fn kind(&self) -> Kind {
+        let (a, b, c) = (self.a as u64, self.b as u64, self.c as u64);
+        let longest = a.max(b).max(c);
+        let others = a + b + c - longest;
+        if others < longest {
+            Kind::Impossible
+        } else if others == longest {
+            Kind::Degenerate
+        } else if a == b && b == c {
+            Kind::Equilateral
+        } else if a == b || b == c || a == c {
+            Kind::Isosceles
+        } else {
+            Kind::Scalene
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    Impossible,
+    // Flat, with the two shorter sides adding up to the longest
+    Degenerate,
+    Scalene,
+    Isosceles,
+    Equilateral
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Counts {
+    pub impossible: u64,
+    pub degenerate: u64,
+    pub scalene: u64,
+    pub isosceles: u64,
+    pub equilateral: u64
+}
+
+impl Counts {
+    fn add(&mut self, kind: Kind) {
+        match kind {
+            Kind::Impossible => self.impossible += 1,
+            Kind::Degenerate => self.degenerate += 1,
+            Kind::Scalene => self.scalene += 1,
+            Kind::Isosceles => self.isosceles += 1,
+            Kind::Equilateral => self.equilateral += 1
+        }
+    }
+
+    // Degenerate triangles don't count, as they have no area
+    pub fn possible(&self) -> u64 {
+        self.scalene + self.isosceles + self.equilateral
+    }
+}
+
+// Counts of each kind of triangle, reading the sides along the rows and down the columns
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Report {
+    pub rows: Counts,
+    pub columns: Counts
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:<12} {:>10} {:>10}", "", "rows", "columns")?;
+        let lines = [
+            ("impossible", self.rows.impossible, self.columns.impossible),
+            ("degenerate", self.rows.degenerate, self.columns.degenerate),
+            ("scalene", self.rows.scalene, self.columns.scalene),
+            ("isosceles", self.rows.isosceles, self.columns.isosceles),
+            ("equilateral", self.rows.equilateral, self.columns.equilateral)
+        ];
+        for &(name, rows, columns) in lines.iter() {
+            writeln!(f, "{:<12} {:>10} {:>10}", name, rows, columns)?;
+        }
+        Ok(())
+    }
+}
+
+// Classifies every triangle in one pass through the input, only holding on to three rows at a time,
+// so the input can be far bigger than memory. The sides are read down the columns, and as every
+// three columns come from the same three rows, the rows can be read back off them.
+pub fn classify<R: BufRead>(input: R) -> Result<Report, ParseError> {
+    let mut report = Report::default();
+    let mut columns = [[0; 3]; 3];
+
+    for (i, sides) in GridReader::<R, 3>::new(input, Order::Columns).enumerate() {
+        let sides = sides?;
+        report.columns.add(Triangle::new(sides[0], sides[1], sides[2]).kind());
+
+        columns[i % 3] = sides;
+        if i % 3 == 2 {
+            for row in 0..3 {
+                report.rows.add(Triangle::new(columns[0][row], columns[1][row], columns[2][row]).kind());
+            }
+        }
+    }
+    Ok(report)
+}
+
+pub fn solve(input: &str) -> Result<(u64, u64), ParseError> {
+    let report = classify(input.as_bytes())?;
+    Ok((report.rows.possible(), report.columns.possible()))
+}
+
+#[test]
+fn solves_puzzle_input() {
+    assert_eq!(solve(include_str!("input.txt")), Ok((862, 1577)));
+}
+
+#[test]
+fn huge_sides_dont_wrap_round() {
+    let max = u32::MAX;
+    assert_eq!(Triangle::new(max, max, max).kind(), Kind::Equilateral);
+    assert_eq!(Triangle::new(max, max, 1).kind(), Kind::Isosceles);
+    // On u32s, max + 2 wraps round to 1, which would make this look impossible
+    assert_eq!(Triangle::new(max, 2, max - 1).kind(), Kind::Scalene);
+    assert_eq!(Triangle::new(max, 1, max - 1).kind(), Kind::Degenerate);
+    assert_eq!(Triangle::new(max, 1, 1).kind(), Kind::Impossible);
+}
+
+#[test]
+fn report_counts_every_kind_both_ways() {
+    let report = classify("3 4 5\n2 2 2\n3 9 3\n".as_bytes()).unwrap();
+    assert_eq!(report.rows, Counts { impossible: 1, degenerate: 0, scalene: 1, isosceles: 0, equilateral: 1 });
+    assert_eq!(report.columns, Counts { impossible: 1, degenerate: 1, scalene: 0, isosceles: 1, equilateral: 0 });
+    assert_eq!(report.to_string(), "                   rows    columns\n\
+impossible            1          1
+degenerate            0          1
+scalene               1          0
+isosceles             0          1
+equilateral           1          0
+");
+}
+
+#[test]
+fn rows_left_over_are_reported_after_the_last_line() {
+    // The blank lines still count, so the error points just past the end of the input
+    assert_eq!(classify("3 4 5\n\n2 2 2\n\n".as_bytes()),
+               Err(ParseError::new(5, 1, "", "Expected 3 rows to read as columns, but there were only 2")));
+}
@@ -0,0 +1,252 @@
+extern crate parse_error;
+extern crate regex;
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use parse_error::ParseError;
+use regex::Regex;
+
+static ALPHA: &'static str = "abcdefghijklmnopqrstuvwxyz";
+
+// Why solve couldn't give both answers
+#[derive(Debug, PartialEq)]
+pub enum SolveError {
+    Parse(ParseError),
+    NoNorthPoleRoom
+}
+
+impl From<ParseError> for SolveError {
+    fn from(error: ParseError) -> SolveError {
+        SolveError::Parse(error)
+    }
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SolveError::Parse(ref error) => write!(f, "{}", error),
+            SolveError::NoNorthPoleRoom => write!(f, "Could not find a room containing North Pole objects")
+        }
+    }
+}
+
+impl Error for SolveError {}
+
+// Returns the sum of valid sector ids, and the sector id of the room where North Pole objects are
+// stored
+pub fn solve(input: &str) -> Result<(u32, u32), SolveError> {
+    let names = parse_names(input)?;
+
+    let north_pole_id = match matching_rooms(&names, &Pattern::Regex(Regex::new("^north").unwrap())).first() {
+        Some(room) => room.id,
+        None => return Err(SolveError::NoNorthPoleRoom)
+    };
+
+    Ok((sum_valid_ids(&names), north_pole_id))
+}
+
+fn parse_names(input: &str) -> Result<Vec<ParsedName<'_>>, ParseError> {
+    input.lines().enumerate()
+        .map(|(i, line)| ParsedName::new(i + 1, line.trim_end()))
+        .collect()
+}
+
+// The five most common letters in a room name, most common first and ties broken alphabetically.
+// Anything which isn't a letter (like the dashes or spaces between words) is ignored.
+pub fn checksum(name: &str) -> String {
+    let mut counts = HashMap::new();
+    for char in name.chars().filter(|&c| ALPHA.contains(c)) {
+        let counter = counts.entry(char).or_insert(0);
+        *counter += 1;
+    }
+
+    let mut char_counts:Vec<(char, u32)> = counts.into_iter().collect();
+    char_counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    char_counts.iter().take(5).map(|&(char, _)| char).collect()
+}
+
+// Moves a lowercase letter the given number of places along the alphabet, wrapping round from z to a
+fn shift(c: char, by: u32) -> char {
+    let pos = ALPHA.chars().position(|a| c == a).unwrap() as u32;
+    ALPHA.chars().nth(((pos + by % 26) % 26) as usize).unwrap()
+}
+
+// Turns a room name of lowercase words separated by spaces into a full encrypted line, e.g.
+// "very encrypted name" in sector 343 becomes "qzmt-zixmtkozy-ivhz-343[zimth]"
+pub fn encrypt(plaintext: &str, id: u32) -> Result<String, ParseError> {
+    let mut words = vec![];
+    for word in plaintext.split(' ') {
+        if word.is_empty() || !word.chars().all(|c| ALPHA.contains(c)) {
+            return Err(ParseError::at(1, plaintext, word, "Expected lowercase words separated by single spaces"));
+        }
+        // Going forward 26 - id places undoes going forward id places
+        words.push(word.chars().map(|c| shift(c, 26 - id % 26)).collect::<String>());
+    }
+
+    let name = words.join("-");
+    let checksum = checksum(&name);
+    Ok(format!("{}-{}[{}]", name, id, checksum))
+}
+
+// What to look for in decrypted room names
+pub enum Pattern {
+    Substring(String),
+    Regex(Regex)
+}
+
+impl Pattern {
+    fn matches(&self, name: &str) -> bool {
+        match *self {
+            Pattern::Substring(ref substring) => name.contains(substring.as_str()),
+            Pattern::Regex(ref regex) => regex.is_match(name)
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Room {
+    pub name: String,
+    pub id: u32
+}
+
+// Every real room (one with a valid checksum) whose decrypted name matches, in the order given
+pub fn search(input: &str, pattern: &Pattern) -> Result<Vec<Room>, ParseError> {
+    Ok(matching_rooms(&parse_names(input)?, pattern))
+}
+
+fn matching_rooms(names: &[ParsedName], pattern: &Pattern) -> Vec<Room> {
+    names.iter()
+        .filter(|name| name.is_valid())
+        .map(|name| Room { name: name.decrypt(), id: name.id })
+        .filter(|room| pattern.matches(&room.name))
+        .collect()
+}
+
+#[derive(Debug)]
+struct ParsedName<'a> {
+    letter_parts: Vec<&'a str>,
+    id: u32,
+    checksum: &'a str
+}
+
+impl<'a> ParsedName<'a> {
+    // Parses e.g. "aaaaa-bbb-z-y-x-123[abxyz]", where line_num is only used for error reporting
+    fn new(line_num: usize, name: &'a str) -> Result<ParsedName<'a>, ParseError> {
+        let last_dash = match name.rfind('-') {
+            Some(idx) => idx,
+            None => return Err(ParseError::new(line_num, 1, name, "Expected dash-separated letters before the sector id"))
+        };
+
+        let letter_parts:Vec<&str> = name[..last_dash].split("-").collect();
+        for letter_part in &letter_parts {
+            if letter_part.is_empty() || !letter_part.chars().all(|c| ALPHA.contains(c)) {
+                return Err(ParseError::at(line_num, name, letter_part, "Expected lowercase letters"));
+            }
+        }
+
+        let number_part = &name[(last_dash + 1)..];
+        let (id_str, checksum) = match (number_part.find('['), number_part.ends_with(']')) {
+            (Some(open), true) => (&number_part[..open], &number_part[(open + 1)..(number_part.len() - 1)]),
+            _ => return Err(ParseError::at(line_num, name, number_part, "Expected a sector id followed by a checksum in square brackets"))
+        };
+
+        let id = match id_str.parse::<u32>() {
+            Ok(id) => id,
+            Err(_) => return Err(ParseError::at(line_num, name, id_str, "Expected a number for the sector id"))
+        };
+
+        Ok(ParsedName {
+            letter_parts: letter_parts,
+            id: id,
+            checksum: checksum
+        })
+    }
+
+    fn is_valid(&self) -> bool {
+        self.checksum == checksum(&self.letter_parts.join("-"))
+    }
+
+    fn decrypt(&self) -> String {
+        self.letter_parts.iter()
+            .map(|letter_part| letter_part.chars().map(|c| shift(c, self.id)).collect::<String>())
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+}
+
+fn sum_valid_ids(names: &[ParsedName]) -> u32 {
+    names.iter().map(|parsed_name| {
+        if parsed_name.is_valid() {
+            Some(parsed_name.id)
+        } else {
+            None
+        }
+    }).fold(0, |acc, id| {
+        if let Some(id_inner) = id {
+            acc + id_inner
+        } else {
+            acc
+        }
+    })
+}
+
+#[test]
+fn sum_of_valid_ids_from_test_input_is_1514() {
+    let test_input = r"aaaaa-bbb-z-y-x-123[abxyz]
+a-b-c-d-e-f-g-h-987[abcde]
+not-a-real-room-404[oarel]
+totally-real-room-200[decoy]";
+
+    assert_eq!(sum_valid_ids(&parse_names(test_input).unwrap()), 1514);
+}
+
+#[test]
+fn qzmt_zixmtkozy_ivhz_343_decrypts_to_very_encrypted_name() {
+    let shift_test = ParsedName {
+        letter_parts: vec!["qzmt", "zixmtkozy", "ivhz"],
+        id: 343,
+        checksum: ""
+    };
+    assert_eq!(shift_test.decrypt(), "very encrypted name");
+}
+
+#[test]
+fn solves_puzzle_input() {
+    assert_eq!(solve(include_str!("input.txt")), Ok((185371, 984)));
+}
+
+#[test]
+fn bad_sector_id_is_reported_with_its_column() {
+    let test_input = r"aaaaa-bbb-z-y-x-123[abxyz]
+a-b-c-d-e-f-g-h-98x[abcde]";
+
+    assert_eq!(solve(test_input),
+               Err(SolveError::Parse(ParseError::new(2, 17, "98x", "Expected a number for the sector id"))));
+}
+
+#[test]
+fn missing_north_pole_room_is_an_error() {
+    assert_eq!(solve("aaaaa-bbb-z-y-x-123[abxyz]"), Err(SolveError::NoNorthPoleRoom));
+}
+
+#[test]
+fn encrypting_gives_a_valid_line_which_decrypts_back() {
+    assert_eq!(checksum("aaaaa-bbb-z-y-x"), "abxyz");
+    assert_eq!(encrypt("very encrypted name", 343), Ok("qzmt-zixmtkozy-ivhz-343[zimth]".to_string()));
+
+    let line = encrypt("north pole objects", 1234).unwrap();
+    assert_eq!(search(&line, &Pattern::Substring("pole".to_string())),
+               Ok(vec![Room { name: "north pole objects".to_string(), id: 1234 }]));
+
+    assert_eq!(encrypt("North Pole", 1), Err(ParseError::new(1, 1, "North", "Expected lowercase words separated by single spaces")));
+}
+
+#[test]
+fn search_finds_rooms_by_substring_or_regex() {
+    let input = include_str!("input.txt");
+    let ids = |pattern: Pattern| search(input, &pattern).unwrap().iter().map(|room| room.id).collect::<Vec<u32>>();
+    assert_eq!(ids(Pattern::Substring("northpole".to_string())), vec![984]);
+    assert_eq!(ids(Pattern::Regex(Regex::new("^north.* storage$").unwrap())), vec![984]);
+}
@@ -1,127 +1,136 @@
-use std::collections::HashMap;
-use std::ops::Index;
-
-static ALPHA: &'static str = "abcdefghijklmnopqrstuvwxyz";
-
-fn main() {
-    let input = include_str!("input.txt");
-
-    println!("Sum of valid ids: {}", sum_valid_ids(input));
-
-    let shift_test = ParsedName {
-        letter_parts: vec!["qzmt", "zixmtkozy", "ivhz"],
-        id: 343,
-        checksum: ""
-    };
-    println!("Test decrypting: {}", shift_test.decrypt());
+extern crate day04;
+extern crate regex;
+
+use std::env;
+use std::fs::File;
+use std::io::{self, Read};
+use std::process;
+
+use day04::{checksum, encrypt, Pattern, search};
+use regex::Regex;
+
+const USAGE: &'static str = "Usage: day04 search <pattern> [--regex] [--input <path>]
+       day04 encrypt <sector id> <name>
+       day04 checksum <name>
+
+search decrypts every real room in the input (stdin unless --input is given) and prints those whose
+names contain the pattern, or match it as a regex with --regex.
+encrypt turns a name of lowercase words separated by spaces into a full room line for the sector.";
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Search { pattern: String, regex: bool, input: Option<String> },
+    Encrypt { id: u32, name: String },
+    Checksum { name: String }
+}
 
-    for line in input.lines() {
-        let name = ParsedName::new(line);
-        if name.is_valid() {
-            let decrypted = name.decrypt();
-            if decrypted.starts_with("north") {
-                println!("{}: {}", name.id, name.decrypt());
-            }
+impl Command {
+    fn parse(args: &[String]) -> Result<Command, String> {
+        let mut args = args.iter();
+
+        match args.next().map(|s| s.as_str()) {
+            Some("search") => {
+                let pattern = match args.next() {
+                    Some(pattern) => pattern.clone(),
+                    None => return Err("No pattern given".to_string())
+                };
+                let mut regex = false;
+                let mut input = None;
+                while let Some(arg) = args.next() {
+                    match arg.as_str() {
+                        "--regex" => regex = true,
+                        "--input" => input = match args.next() {
+                            Some(path) => Some(path.clone()),
+                            None => return Err("--input needs a path".to_string())
+                        },
+                        other => return Err(format!("Unexpected argument: {}", other))
+                    }
+                }
+                Ok(Command::Search { pattern: pattern, regex: regex, input: input })
+            },
+            Some("encrypt") => {
+                let id = match args.next().map(|id| id.parse::<u32>()) {
+                    Some(Ok(id)) => id,
+                    _ => return Err("Expected a sector id".to_string())
+                };
+                // The rest of the arguments make up the name, so it doesn't need quoting
+                let name:Vec<&str> = args.map(|s| s.as_str()).collect();
+                if name.is_empty() {
+                    return Err("No name given".to_string());
+                }
+                Ok(Command::Encrypt { id: id, name: name.join(" ") })
+            },
+            Some("checksum") => {
+                let name:Vec<&str> = args.map(|s| s.as_str()).collect();
+                if name.is_empty() {
+                    return Err("No name given".to_string());
+                }
+                Ok(Command::Checksum { name: name.join(" ") })
+            },
+            Some(other) => Err(format!("Unknown command: {}", other)),
+            None => Err("No command given".to_string())
         }
     }
 }
 
-#[derive(Debug)]
-struct CharCount<'a> {
-    char: &'a char,
-    count: &'a u32
-}
-
-#[derive(Debug)]
-struct ParsedName<'a> {
-    letter_parts: Vec<&'a str>,
-    id: u32,
-    checksum: &'a str
+fn read_input(path: &Option<String>) -> Result<String, String> {
+    let mut input = String::new();
+    let result = match *path {
+        Some(ref path) => File::open(path).and_then(|mut file| file.read_to_string(&mut input)),
+        None => io::stdin().read_to_string(&mut input)
+    };
+    result.map_err(|e| format!("Couldn't read the input: {}", e))?;
+    Ok(input)
 }
 
-impl<'a> ParsedName<'a> {
-    fn new(name: &str) -> ParsedName {
-        let parts = name.split("-");
-        let (letter_parts, number_parts):(Vec<&str>, Vec<&str>) = parts.partition(
-            |p| p.starts_with(|c:char| c.is_alphabetic())
-        );
-        let number_part = number_parts[0];
-
-        let number_part_splits:Vec<&str> = number_part.split("[").collect();
-        let id_str = number_part_splits[0];
-        let checksum_splits:Vec<&str> = number_part_splits[1].split("]").collect();
-        let checksum:&str = checksum_splits[0];
-
-        ParsedName {
-            letter_parts: letter_parts,
-            id: id_str.parse::<u32>().unwrap(),
-            checksum: checksum
-        }
-    }
-
-    fn is_valid(&self) -> bool {
-        let mut counts = HashMap::new();
-        for letter_part in &self.letter_parts {
-            for char in letter_part.chars() {
-                let counter = counts.entry(char).or_insert(0);
-                *counter += 1;
-            }
-        }
-
-        let mut char_counts:Vec<CharCount> = counts.iter()
-            .map(|(k, v)| CharCount{ char: k, count: v })
-            .collect();
-        char_counts.sort_by(|a, b| {
-            let count_ord = b.count.cmp(a.count);
-            if count_ord == std::cmp::Ordering::Equal {
-                a.char.cmp(b.char)
+fn run(command: Command) -> Result<(), String> {
+    match command {
+        Command::Search { pattern, regex, input } => {
+            let pattern = if regex {
+                Pattern::Regex(Regex::new(&pattern).map_err(|e| format!("Bad regex: {}", e))?)
             } else {
-                count_ord
+                Pattern::Substring(pattern)
+            };
+            let input = read_input(&input)?;
+            for room in search(&input, &pattern).map_err(|e| e.to_string())? {
+                println!("{:>6}  {}", room.id, room.name);
             }
-        });
-
-        let expected_checksum:String = char_counts.index(0..5).iter()
-            .map(|cc| cc.char.clone()) // convert &char to char
-            .collect(); // concatenate to String
-
-        self.checksum.eq(&expected_checksum)
+        },
+        Command::Encrypt { id, name } => println!("{}", encrypt(&name, id).map_err(|e| e.to_string())?),
+        Command::Checksum { name } => println!("{}", checksum(&name))
     }
+    Ok(())
+}
 
-    fn decrypt(&self) -> String {
-        (&self.letter_parts).into_iter().map(|letter_part| {
-            letter_part.chars().map(|c| {
-                let pos = ALPHA.chars().position(|a| c == a).unwrap();
-                ALPHA.chars().nth((pos + self.id as usize) % 26).unwrap().clone()
-            }).collect::<String>()
-        }).collect::<Vec<String>>()
-            .join(" ")
+fn main() {
+    let args:Vec<String> = env::args().skip(1).collect();
+
+    let command = match Command::parse(&args) {
+        Ok(command) => command,
+        Err(message) => {
+            eprintln!("{}", message);
+            eprintln!("{}", USAGE);
+            process::exit(1);
+        }
+    };
+
+    if let Err(message) = run(command) {
+        eprintln!("{}", message);
+        process::exit(1);
     }
 }
 
-fn sum_valid_ids(input: &str) -> u32 {
-    input.lines().map(|name| {
-        let parsed_name = ParsedName::new(name);
-
-        if parsed_name.is_valid() {
-            Some(parsed_name.id)
-        } else {
-            None
-        }
-    }).fold(0, |acc, id| {
-        if let Some(id_inner) = id {
-            acc + id_inner
-        } else {
-            acc
-        }
-    })
+#[cfg(test)]
+fn to_args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|s| s.to_string()).collect()
 }
 
 #[test]
-fn sum_of_valid_ids_from_test_input_is_1514() {
-    let test_input = r"aaaaa-bbb-z-y-x-123[abxyz]
-a-b-c-d-e-f-g-h-987[abcde]
-not-a-real-room-404[oarel]
-totally-real-room-200[decoy]";
-
-    assert_eq!(sum_valid_ids(test_input), 1514);
-}
\ No newline at end of file
+fn commands_parse_with_their_options() {
+    assert_eq!(Command::parse(&to_args(&["search", "^north", "--regex", "--input", "day04.txt"])),
+               Ok(Command::Search { pattern: "^north".to_string(), regex: true, input: Some("day04.txt".to_string()) }));
+    assert_eq!(Command::parse(&to_args(&["encrypt", "343", "very", "encrypted", "name"])),
+               Ok(Command::Encrypt { id: 343, name: "very encrypted name".to_string() }));
+    assert!(Command::parse(&to_args(&["encrypt", "north", "pole"])).is_err());
+    assert!(Command::parse(&to_args(&["search", "north", "--regs"])).is_err());
+}
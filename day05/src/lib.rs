@@ -0,0 +1,214 @@
+extern crate crypto;
+
+use std::io::{self, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crypto::md5::*;
+use crypto::digest::Digest;
+
+// How many counters each worker hashes before the results are merged
+const CHUNK_SIZE: u64 = 50000;
+
+// Smaller chunks when animated, so there's a fresh hash to flicker with for every frame
+const ANIMATED_CHUNK_SIZE: u64 = 5000;
+
+// How often the animation is redrawn
+const FRAME_INTERVAL: Duration = Duration::from_millis(50);
+
+// A counter whose hash starts with enough zeros, and the next two characters of that hash
+type Find = (u64, char, char);
+
+// How to show the passwords being worked out: not at all, a line for each new character, or the
+// second password redrawn in place like a film's hacker, with the characters still to find flickering
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Output { Quiet, Log, Animated }
+
+// Both passwords, filled in from the interesting hashes in the order they're found
+struct Passwords {
+    answer: String,
+    answer2: [char; 8],
+    answer2_count: usize,
+    output: Output,
+    last_frame: Option<Instant>
+}
+
+impl Passwords {
+    fn new(output: Output) -> Passwords {
+        Passwords {
+            answer: String::new(),
+            answer2: ['-'; 8],
+            answer2_count: 0,
+            output: output,
+            last_frame: None
+        }
+    }
+
+    // Takes the sixth and seventh characters of the next interesting hash
+    fn add(&mut self, sixth: char, seventh: char) {
+        if self.answer.len() < 8 {
+            self.answer.push(sixth);
+            if self.output == Output::Log {
+                println!("NEW DIGIT: {}", self.answer);
+            }
+        }
+
+        if let Some(sixth_digit) = sixth.to_digit(10) {
+            if sixth_digit < 8 && self.answer2[sixth_digit as usize] == '-' {
+                self.answer2[sixth_digit as usize] = seventh;
+                self.answer2_count += 1;
+                if self.output == Output::Log {
+                    println!("NEW DIGIT 2: {:?}", self.answer2);
+                }
+            }
+        }
+    }
+
+    // The second password with the characters found so far in bold, and the rest taken from
+    // whatever the latest hash was so they change every frame
+    fn frame(&self, hash: &str) -> String {
+        self.answer2.iter().zip(hash.chars()).map(|(&found, random)| {
+            if found == '-' { random.to_string() } else { format!("\x1b[1m{}\x1b[0m", found) }
+        }).collect()
+    }
+
+    // Redraws the second password over the last frame when animated, at most once a frame interval
+    // apart from the final frame
+    fn show(&mut self, hash: &str) {
+        let due = match self.last_frame {
+            Some(last) => last.elapsed() >= FRAME_INTERVAL,
+            None => true
+        };
+        if self.output == Output::Animated && (due || self.is_done()) {
+            self.last_frame = Some(Instant::now());
+            print!("\r{}", self.frame(hash));
+            if self.is_done() {
+                println!();
+            }
+            io::stdout().flush().ok();
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.answer.len() == 8 && self.answer2_count == 8
+    }
+
+    fn answers(&self) -> (String, String) {
+        (self.answer.clone(), self.answer2.iter().collect())
+    }
+}
+
+// Hashes the door id followed by the counter, leaving md5 reset for the next one
+fn door_hash(md5: &mut Md5, door_id: &str, count: u64) -> String {
+    md5.input_str(door_id);
+    md5.input_str(&count.to_string());
+    let result = md5.result_str();
+    md5.reset();
+    result
+}
+
+// Hashes the door id with every counter from start up to end, returning the counter and the sixth
+// and seventh characters of each hash starting with enough zeros, along with the last hash
+fn find_hashes(door_id: &str, zeros: usize, start: u64, end: u64) -> (Vec<Find>, String) {
+    let mut md5 = Md5::new();
+    let prefix:String = vec!['0'; zeros].into_iter().collect();
+    let mut found = vec![];
+    let mut result = String::new();
+
+    for count in start..end {
+        result = door_hash(&mut md5, door_id, count);
+
+        if result.starts_with(&prefix) {
+            let mut rest = result.chars().skip(zeros);
+            found.push((count, rest.next().unwrap(), rest.next().unwrap()));
+        }
+    }
+    (found, result)
+}
+
+// Each round gives every worker the next chunk of counters, then merges what they found in counter
+// order, so the passwords come out the same as hashing one counter at a time. The animation is
+// drawn between rounds, when enough time has passed since the last frame.
+fn crack(door_id: &str, zeros: usize, workers: u64, chunk_size: u64, output: Output) -> (String, String) {
+    let mut passwords = Passwords::new(output);
+    let mut start = 0;
+
+    while !passwords.is_done() {
+        let found:Vec<(Vec<Find>, String)> = thread::scope(|scope| {
+            let handles:Vec<_> = (0..workers).map(|worker| {
+                let chunk_start = start + worker * chunk_size;
+                scope.spawn(move || find_hashes(door_id, zeros, chunk_start, chunk_start + chunk_size))
+            }).collect();
+            handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+        });
+
+        let latest = found.last().map(|(_, hash)| hash.clone()).unwrap_or_default();
+        for (_, sixth, seventh) in found.into_iter().flat_map(|(hashes, _)| hashes) {
+            passwords.add(sixth, seventh);
+        }
+        passwords.show(&latest);
+        start += workers * chunk_size;
+    }
+
+    passwords.answers()
+}
+
+// Hashes one counter at a time with a single Md5, to check crack against
+fn crack_sequentially(door_id: &str, zeros: usize) -> (String, String) {
+    let mut passwords = Passwords::new(Output::Quiet);
+    let mut count = 0;
+
+    while !passwords.is_done() {
+        for (_, sixth, seventh) in find_hashes(door_id, zeros, count, count + 1).0 {
+            passwords.add(sixth, seventh);
+        }
+        count += 1;
+    }
+
+    passwords.answers()
+}
+
+pub fn solve(input: &str) -> (String, String) {
+    solve_with_output(input, Output::Quiet)
+}
+
+pub fn solve_with_output(input: &str, output: Output) -> (String, String) {
+    let workers = thread::available_parallelism().map(|n| n.get() as u64).unwrap_or(4);
+    let chunk_size = if output == Output::Animated { ANIMATED_CHUNK_SIZE } else { CHUNK_SIZE };
+    crack(input.trim(), 5, workers, chunk_size, output)
+}
+
+pub fn solve_sequentially(input: &str) -> (String, String) {
+    crack_sequentially(input.trim(), 5)
+}
+
+#[test]
+fn parallel_and_sequential_passwords_are_the_same() {
+    // Three zeros rather than five, so there's plenty to find quickly, and small chunks so the
+    // passwords are spread over many rounds
+    let sequential = crack_sequentially("abc", 3);
+    assert_eq!(crack("abc", 3, 4, 1000, Output::Quiet), sequential);
+    assert_eq!(crack("abc", 3, 3, 777, Output::Quiet), sequential);
+}
+
+#[test]
+fn animation_frame_shows_found_characters_and_flickers_the_rest() {
+    let mut passwords = Passwords::new(Output::Animated);
+    passwords.add('1', '5');
+    passwords.add('6', 'e');
+    assert_eq!(passwords.frame("0123456789abcdef"), "0\x1b[1m5\x1b[0m2345\x1b[1me\x1b[0m7");
+}
+
+#[test]
+fn door_id_is_hashed_every_time_md5_is_reused() {
+    // The first counter giving five zeros for the example door id
+    let mut md5 = Md5::new();
+    assert!(door_hash(&mut md5, "abc", 3231929).starts_with("000001"));
+    assert!(door_hash(&mut md5, "abc", 3231929).starts_with("000001"));
+}
+
+#[test]
+#[ignore] // Millions of MD5 hashes, too slow without optimisations
+fn solves_puzzle_input() {
+    assert_eq!(solve(include_str!("input.txt")), ("f97c354d".to_string(), "863dde27".to_string()));
+}
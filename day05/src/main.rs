@@ -1,50 +1,81 @@
-extern crate crypto;
+extern crate day05;
 
-use crypto::md5::*;
-use crypto::digest::Digest;
+use std::env;
+use std::fs::File;
+use std::io::{self, Read};
+use std::process;
 
-fn main() {
-    let mut md5 = Md5::new();
-    md5.input_str("reyedfim");
+use day05::{Output, solve_with_output};
 
-    let mut answer = String::new();
-    let mut answer2 = ['-'; 8];
-    let mut count = 0;
-    let mut answer2_count = 0;
+const USAGE: &'static str = "Usage: day05 [--log | --animate] [--input <path>]
 
-    while answer.len() < 8 || answer2_count < 8 {
-        md5.input_str(&count.to_string());
-        let result = md5.result_str();
+Works out both door passwords for the door id in the input (stdin unless --input is given).
+--log prints each new character as it's found, and --animate redraws the second password in place.";
 
-        if result.starts_with("00000") {
-            let sixth = result.chars().nth(5).unwrap();
+#[derive(Debug, PartialEq)]
+struct Args {
+    output: Output,
+    input: Option<String>
+}
 
-            if answer.len() < 8 {
-                answer.push(sixth);
-                println!("NEW DIGIT: {}", answer);
-            }
+impl Args {
+    fn parse(args: &[String]) -> Result<Args, String> {
+        let mut args = args.iter();
+        let mut output = Output::Quiet;
+        let mut input = None;
 
-            match sixth.to_digit(10) {
-                Some(sixth_digit) => {
-                    if sixth_digit < 8 && answer2[sixth_digit as usize] == '-' {
-                        let seventh = result.chars().nth(6).unwrap();
-                        answer2[sixth_digit as usize] = seventh;
-                        answer2_count += 1;
-                        println!("NEW DIGIT 2: {:?}", answer2);
-                    }
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--log" => output = Output::Log,
+                "--animate" => output = Output::Animated,
+                "--input" => input = match args.next() {
+                    Some(path) => Some(path.clone()),
+                    None => return Err("--input needs a path".to_string())
                 },
-                None => {}
+                other => return Err(format!("Unexpected argument: {}", other))
             }
         }
+        Ok(Args { output: output, input: input })
+    }
+}
 
-        if count % 50000 == 0 {
-            println!("{}", count);
+fn main() {
+    let args:Vec<String> = env::args().skip(1).collect();
+
+    let args = match Args::parse(&args) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{}", message);
+            eprintln!("{}", USAGE);
+            process::exit(1);
         }
+    };
 
-        md5.reset();
-        count += 1;
+    let mut input = String::new();
+    let result = match args.input {
+        Some(ref path) => File::open(path).and_then(|mut file| file.read_to_string(&mut input)),
+        None => io::stdin().read_to_string(&mut input)
+    };
+    if let Err(e) = result {
+        eprintln!("Couldn't read the input: {}", e);
+        process::exit(1);
     }
 
-    println!("Answer: {}", answer);
-    println!("Answer2: {:?}", answer2);
+    let (answer, answer2) = solve_with_output(&input, args.output);
+    println!("Part 1: {}", answer);
+    println!("Part 2: {}", answer2);
+}
+
+#[cfg(test)]
+fn to_args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn output_and_input_are_optional() {
+    assert_eq!(Args::parse(&[]), Ok(Args { output: Output::Quiet, input: None }));
+    assert_eq!(Args::parse(&to_args(&["--log"])), Ok(Args { output: Output::Log, input: None }));
+    assert_eq!(Args::parse(&to_args(&["--animate", "--input", "day05.txt"])),
+               Ok(Args { output: Output::Animated, input: Some("day05.txt".to_string()) }));
+    assert!(Args::parse(&to_args(&["--input"])).is_err());
 }
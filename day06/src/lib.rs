@@ -0,0 +1,38 @@
+use std::collections::HashMap;
+
+pub fn solve(input: &str) -> (String, String) {
+    let input_width = input.lines().next().map(|line| line.len()).unwrap_or(0);
+
+    let mut maps:Vec<HashMap<char, usize>> = vec![];
+    for _ in 0..input_width {
+        maps.push(HashMap::new());
+    }
+
+    for line in input.lines() {
+        for (index, char) in line.chars().enumerate() {
+            let map:&mut HashMap<char, usize> = &mut maps[index];
+            let count = map.entry(char).or_insert(0);
+            *count += 1;
+        }
+    }
+
+    let part1:String = maps.iter().map(|map| {
+        *map.iter().max_by_key(|&(_, count)| count).unwrap().0
+    }).collect();
+
+    let part2:String = maps.iter().map(|map| {
+        *map.iter().min_by_key(|&(_, count)| count).unwrap().0
+    }).collect();
+
+    (part1, part2)
+}
+
+#[test]
+fn test_input_gives_easter_and_advent() {
+    assert_eq!(solve(include_str!("test-input.txt")), ("easter".to_string(), "advent".to_string()));
+}
+
+#[test]
+fn solves_puzzle_input() {
+    assert_eq!(solve(include_str!("input.txt")), ("ursvoerv".to_string(), "vomaypnn".to_string()));
+}
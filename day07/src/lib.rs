@@ -1,12 +1,10 @@
 use std::collections::HashSet;
 
-fn main() {
-    let input = include_str!("input.txt");
-
+pub fn solve(input: &str) -> (usize, usize) {
     let mut tls_count = 0;
     let mut ssl_count = 0;
     for line in input.lines() {
-        let parts = line.split(|c| c == '[' || c == ']');
+        let parts = line.split(['[', ']']);
 
         let mut has_abba_outside_brackets = false;
         let mut has_abba_inside_brackets = false;
@@ -44,8 +42,7 @@ fn main() {
         }
     }
 
-    println!("TLS count: {}", tls_count);
-    println!("SSL count: {}", ssl_count);
+    (tls_count, ssl_count)
 }
 
 fn has_abba(str: &str) -> bool {
@@ -85,7 +82,7 @@ fn get_abs(str: &str) -> Option<Vec<String>> {
         c = chars.next();
     }
 
-    if result.len() > 0 {
+    if !result.is_empty() {
         Some(result)
     } else {
         None
@@ -109,9 +106,20 @@ fn get_bas(str: &str) -> Option<Vec<String>> {
         c = chars.next();
     }
 
-    if result.len() > 0 {
+    if !result.is_empty() {
         Some(result)
     } else {
         None
     }
 }
+
+#[test]
+fn test_inputs_support_tls_and_ssl() {
+    assert_eq!(solve(include_str!("input-test.txt")).0, 2);
+    assert_eq!(solve(include_str!("input-test2.txt")).1, 3);
+}
+
+#[test]
+fn solves_puzzle_input() {
+    assert_eq!(solve(include_str!("input.txt")), (115, 231));
+}
@@ -12,20 +12,15 @@ enum Instruction {
     RotateCol{index: usize, shift: usize}
 }
 
-fn main() {
+// Returns the number of lit pixels, and the final screen (which shows the code as ASCII art)
+pub fn solve(instrs: &str) -> (usize, String) {
     let rect_re = Regex::new(r"rect (?P<width>\d+)x(?P<height>\d+)").unwrap();
     let rotate_row_re = Regex::new(r"rotate row y=(?P<index>\d+) by (?P<shift>\d+)").unwrap();
     let rotate_col_re = Regex::new(r"rotate column x=(?P<index>\d+) by (?P<shift>\d+)").unwrap();
 
-    let instrs = include_str!("input.txt");
-
     let mut screen = vec![];
     for _ in 0..HEIGHT {
-        let mut row = vec![];
-        for _ in 0..WIDTH {
-            row.push(false);
-        }
-        screen.push(row);
+        screen.push(vec![false; WIDTH]);
     }
 
     for instr_str in instrs.lines() {
@@ -64,7 +59,7 @@ fn main() {
                 let count_before = count(&screen);
 
                 {
-                    let ref mut row = screen[index];
+                    let row = &mut screen[index];
                     for _ in 0..shift {
                         let pixel = row.pop().unwrap();
                         row.insert(0, pixel);
@@ -94,30 +89,23 @@ fn main() {
                 }
             }
         }
-
-        println!("{:?}", instr);
-        for row in 0..HEIGHT {
-            for col in 0..WIDTH {
-                print!("{}", if screen[row][col] { '#' } else { '.' });
-            }
-            println!("");
-        }
-        println!("");
     }
 
-    let count = count(&screen);
+    (count(&screen), render(&screen))
+}
+
+fn render(screen: &[Vec<bool>]) -> String {
+    let mut result = String::new();
     for row in 0..HEIGHT {
         for col in 0..WIDTH {
-            print!("{}", if screen[row][col] { '#' } else { '.' });
+            result.push(if screen[row][col] { '#' } else { '.' });
         }
-        println!("");
+        result.push('\n');
     }
-    println!("");
-
-    println!("Final count: {}", count);
+    result
 }
 
-fn count(screen: &Vec<Vec<bool>>) -> usize {
+fn count(screen: &[Vec<bool>]) -> usize {
     let mut count = 0;
     for row in 0..HEIGHT {
         for col in 0..WIDTH {
@@ -127,4 +115,9 @@ fn count(screen: &Vec<Vec<bool>>) -> usize {
         }
     }
     count
-}
\ No newline at end of file
+}
+
+#[test]
+fn solves_puzzle_input() {
+    assert_eq!(solve(include_str!("input.txt")).0, 110);
+}
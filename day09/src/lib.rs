@@ -26,7 +26,7 @@ fn decompress(input: &str, mode: &Mode) -> usize {
         match *mode {
             Mode::Recursive => {
                 // Recursively decompress
-                let child_expanded_len = decompress(&current_slice[instr_end+1..(instr_end+1+encoded_run_len)], &mode);
+                let child_expanded_len = decompress(&current_slice[instr_end+1..(instr_end+1+encoded_run_len)], mode);
                 expanded_len += num_repeats * child_expanded_len;
             },
             Mode::Simple => {
@@ -44,9 +44,19 @@ fn decompress(input: &str, mode: &Mode) -> usize {
     expanded_len
 }
 
-fn main() {
-    let input = include_str!("input.txt");
+pub fn solve(input: &str) -> (usize, usize) {
+    let input = input.trim();
+    (decompress(input, &Mode::Simple), decompress(input, &Mode::Recursive))
+}
+
+#[test]
+fn x8_and_x6_markers_decompress_recursively() {
+    assert_eq!(decompress("X(8x2)(3x3)ABCY", &Mode::Simple), 18);
+    assert_eq!(decompress("X(8x2)(3x3)ABCY", &Mode::Recursive), 20);
+    assert_eq!(decompress("(27x12)(20x12)(13x14)(7x10)(1x12)A", &Mode::Recursive), 241920);
+}
 
-    println!("Part 1: {}", decompress(input, &Mode::Simple));
-    println!("Part 2: {}", decompress(input, &Mode::Recursive));
-}
\ No newline at end of file
+#[test]
+fn solves_puzzle_input() {
+    assert_eq!(solve(include_str!("input.txt")), (138735, 11125026826));
+}
@@ -2,6 +2,9 @@ extern crate regex;
 use regex::Regex;
 use std::collections::HashMap;
 
+extern crate parse_error;
+use parse_error::ParseError;
+
 #[derive(Copy, Clone)]
 enum Destination {
     Bot(usize),
@@ -14,15 +17,21 @@ struct Move {
     dest: Destination
 }
 
+// The regexes only match digits, but the number could still be too big
+fn parse_number(caps: &regex::Captures, name: &str, line_num: usize, line: &str) -> Result<usize, ParseError> {
+    let text = caps.name(name).unwrap();
+    text.parse::<usize>().map_err(|_| ParseError::at(line_num, line, text, "Number is too large"))
+}
+
 impl Move {
-    fn from_captures(caps:regex::Captures) -> Move {
-        let val = caps.name("val").unwrap().parse::<usize>().unwrap();
-        let bot = caps.name("bot").unwrap().parse::<usize>().unwrap();
+    fn from_captures(caps:regex::Captures, line_num: usize, line: &str) -> Result<Move, ParseError> {
+        let val = parse_number(&caps, "val", line_num, line)?;
+        let bot = parse_number(&caps, "bot", line_num, line)?;
 
-        Move {
+        Ok(Move {
             value: val,
             dest: Destination::Bot(bot)
-        }
+        })
     }
 
     fn new(val: usize, dest: Destination) -> Move {
@@ -41,11 +50,11 @@ struct Bot {
 }
 
 impl Bot {
-    fn from_captures(caps:regex::Captures) -> (usize, Bot) {
-        let cur_bot = caps.name("cur_bot").unwrap().parse::<usize>().unwrap();
+    fn from_captures(caps:regex::Captures, line_num: usize, line: &str) -> Result<(usize, Bot), ParseError> {
+        let cur_bot = parse_number(&caps, "cur_bot", line_num, line)?;
 
         let low_type = caps.name("low_type").unwrap();
-        let low_id = caps.name("low_id").unwrap().parse::<usize>().unwrap();
+        let low_id = parse_number(&caps, "low_id", line_num, line)?;
         let low_dest = if low_type == "bot" {
             Destination::Bot(low_id)
         } else if low_type == "output" {
@@ -55,7 +64,7 @@ impl Bot {
         };
 
         let high_type = caps.name("high_type").unwrap();
-        let high_id = caps.name("high_id").unwrap().parse::<usize>().unwrap();
+        let high_id = parse_number(&caps, "high_id", line_num, line)?;
         let high_dest = if high_type == "bot" {
             Destination::Bot(high_id)
         } else if high_type == "output" {
@@ -64,7 +73,7 @@ impl Bot {
             panic!("Unexpected destination type: {}", high_type)
         };
 
-        (cur_bot, Self::new(cur_bot, low_dest, high_dest))
+        Ok((cur_bot, Self::new(cur_bot, low_dest, high_dest)))
     }
 
     fn new(id: usize, low_dest: Destination, high_dest: Destination) -> Bot {
@@ -113,35 +122,40 @@ impl Factory {
         }
     }
 
-    fn parse_instructions(&mut self, file: &str) {
-        let give_value_re = Regex::new(r"value (?P<val>\d+) goes to bot (?P<bot>\d+)").unwrap();
-        let low_high_re:Regex = Regex::new(r"bot (?P<cur_bot>\d+) gives low to (?P<low_type>bot|output) (?P<low_id>\d+) and high to (?P<high_type>bot|output) (?P<high_id>\d+)").unwrap();
+    fn parse_instructions(&mut self, file: &str) -> Result<(), ParseError> {
+        let give_value_re = Regex::new(r"^value (?P<val>\d+) goes to bot (?P<bot>\d+)$").unwrap();
+        let low_high_re:Regex = Regex::new(r"^bot (?P<cur_bot>\d+) gives low to (?P<low_type>bot|output) (?P<low_id>\d+) and high to (?P<high_type>bot|output) (?P<high_id>\d+)$").unwrap();
 
-        for line in file.lines() {
+        for (i, line) in file.lines().enumerate() {
+            let line = line.trim_end();
             if let Some(caps) = low_high_re.captures(line) {
-                let (id, bot) = Bot::from_captures(caps);
+                let (id, bot) = Bot::from_captures(caps, i + 1, line)?;
                 self.bots.insert(id, bot);
             } else if let Some(caps) = give_value_re.captures(line) {
-                self.moves.push(Move::from_captures(caps))
+                self.moves.push(Move::from_captures(caps, i + 1, line)?)
             } else {
-                panic!("Unrecognised line: {}", line);
+                return Err(ParseError::new(i + 1, 1, line, "Unrecognised line"));
             }
         }
+
+        Ok(())
     }
 
-    fn process_moves(&mut self, watch_for_low: usize, watch_for_high: usize) {
-        while self.moves.len() > 0 {
+    // Returns the id of the bot which compares the two watched values, if any
+    fn process_moves(&mut self, watch_for_low: usize, watch_for_high: usize) -> Option<usize> {
+        let mut watched_bot = None;
+
+        while !self.moves.is_empty() {
             let m:Move = self.moves.remove(0);
             match m.dest {
                 Destination::Bot(id) => {
-                    let ref mut bot = self.bots.get_mut(&id).unwrap();
+                    let bot = self.bots.get_mut(&id).unwrap();
                     if let Some((m1, m2)) = bot.add_value(m.value) {
                         self.moves.push(m1);
                         self.moves.push(m2);
 
                         if m1.value == watch_for_low && m2.value == watch_for_high {
-                            println!("Bot {} responsible for comparing {} & {}",
-                                     id, watch_for_low, watch_for_high);
+                            watched_bot = Some(id);
                         }
                     };
                 },
@@ -152,6 +166,8 @@ impl Factory {
                 }
             }
         }
+
+        watched_bot
     }
 
     fn out(&self, i: usize) -> &usize {
@@ -163,29 +179,35 @@ impl Factory {
     }
 }
 
-fn main() {
-    println!("Example factory:");
-
-    let test_file = include_str!("input-test.txt");
-
-    let mut test_factory = Factory::new();
-    test_factory.parse_instructions(test_file);
-
-    test_factory.process_moves(2, 5);
-
-    println!("Outputs 0, 1, 2: [{}, {}, {}]", test_factory.out(0), test_factory.out(1), test_factory.out(2));
+// Returns the bot which compares microchips 17 & 61, and the product of outputs 0, 1 and 2
+pub fn solve(file: &str) -> Result<(usize, usize), ParseError> {
+    let mut factory = Factory::new();
+    factory.parse_instructions(file)?;
 
-    // --------
+    let bot = factory.process_moves(17, 61).expect("No bot compared microchips 17 & 61");
 
-    println!("");
-    println!("Real factory:");
+    Ok((bot, factory.multiply_outputs(0, 1, 2)))
+}
 
-    let file = include_str!("input.txt");
+#[test]
+fn example_factory_has_bot_2_comparing_2_and_5() {
+    let mut test_factory = Factory::new();
+    test_factory.parse_instructions(include_str!("input-test.txt")).unwrap();
 
-    let mut factory = Factory::new();
-    factory.parse_instructions(file);
+    assert_eq!(test_factory.process_moves(2, 5), Some(2));
+    assert_eq!((*test_factory.out(0), *test_factory.out(1), *test_factory.out(2)), (5, 2, 3));
+}
 
-    factory.process_moves(17, 61);
+#[test]
+fn solves_puzzle_input() {
+    assert_eq!(solve(include_str!("input.txt")), Ok((147, 55637)));
+}
 
-    println!("Output 0 * 1 * 2 = {}", factory.multiply_outputs(0, 1, 2));
+#[test]
+fn unrecognised_line_is_reported() {
+    let file = r"value 5 goes to bot 2
+bot 2 gives low to bot 1 and high to bot 0
+bot 1 gives low to outptu 1 and high to bot 0";
+    let error = Factory::new().parse_instructions(file).unwrap_err();
+    assert_eq!((error.line, error.column), (3, 1));
 }
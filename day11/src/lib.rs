@@ -0,0 +1,420 @@
+extern crate parse_error;
+extern crate regex;
+use parse_error::ParseError;
+use regex::Regex;
+
+use std::collections::VecDeque;
+use std::collections::HashSet;
+
+const NUM_FLOORS:usize = 4;
+const NUM_HASH_PARTS:usize = NUM_FLOORS*NUM_FLOORS + 1;
+
+#[derive(Debug)]
+enum ComponentType {
+    Generator,
+    Microchip
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Pair {
+    element: char,
+    gen_floor: usize,
+    chip_floor: usize
+}
+
+impl Pair {
+    fn new(element: char, gf: usize, cf: usize) -> Pair {
+        Pair {
+            element: element,
+            gen_floor: gf,
+            chip_floor: cf
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Item {
+    pair: Pair,
+    c_type: ComponentType
+}
+
+impl Item {
+    fn floor(&self) -> usize {
+        match self.c_type {
+            ComponentType::Generator => self.pair.gen_floor,
+            ComponentType::Microchip => self.pair.chip_floor
+        }
+    }
+}
+
+struct State {
+    moves: usize,
+    elevator_floor: usize,
+    pairs: Vec<Pair>
+}
+
+
+impl State {
+    fn new(elevator_floor: usize, pairs: Vec<Pair>) -> State {
+        State {
+            moves: 0,
+            elevator_floor: elevator_floor,
+            pairs: pairs
+        }
+    }
+
+    fn print(&self) {
+        for i in 0..NUM_FLOORS {
+            let floor = NUM_FLOORS - 1 - i;
+
+            print!("F{} ", floor + 1);
+
+            if floor == self.elevator_floor {
+                print!("E  ");
+            } else {
+                print!(".  ");
+            }
+
+            for i in 0..self.pairs.len() {
+                let pair = self.pairs[i];
+                if floor == pair.gen_floor {
+                    print!("{}G ", pair.element)
+                } else {
+                    print!(".  ");
+                }
+                if floor == pair.chip_floor {
+                    print!("{}M ", pair.element)
+                } else {
+                    print!(".  ");
+                }
+            }
+
+            println!();
+        }
+    }
+
+    fn hash_counts(&self) -> [u32; NUM_HASH_PARTS] {
+        let mut counts = [0u32; NUM_HASH_PARTS];
+
+        counts[0] = self.elevator_floor as u32;
+        for i in 0..self.pairs.len() {
+            let pair = self.pairs[i];
+            counts[pair.gen_floor*4 + pair.chip_floor + 1] += 1;
+        }
+
+        counts
+    }
+
+    fn is_end_state(&self) -> bool {
+        for i in 0..self.pairs.len() {
+            let pair = self.pairs[i];
+            if pair.gen_floor != NUM_FLOORS - 1 || pair.chip_floor != NUM_FLOORS - 1 {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn move_items(&self, item1: &Item, item2: &Option<Item>, to_floor: usize) -> State {
+        if item1.floor() != self.elevator_floor {
+            self.print();
+            panic!("Expected {:?} to start on floor index {} (to {}) but it isn't", item1, self.elevator_floor, to_floor);
+        }
+        if let Some(i2) = item2 {
+            if i2.floor() != self.elevator_floor {
+                self.print();
+                panic!("Expected {:?} to start on floor index {} (to {}) but it isn't", i2, self.elevator_floor, to_floor);
+            }
+        }
+
+        let mut new_pairs = self.pairs.clone();
+        for pair in &mut new_pairs {
+            if pair.element == item1.pair.element {
+                match item1.c_type {
+                    ComponentType::Microchip => {
+                        pair.chip_floor = to_floor
+                    },
+                    ComponentType::Generator => {
+                        pair.gen_floor = to_floor
+                    }
+                }
+            }
+            if let Some(i) = item2 {
+                if pair.element == i.pair.element {
+                    match i.c_type {
+                        ComponentType::Microchip => {
+                            pair.chip_floor = to_floor
+                        },
+                        ComponentType::Generator => {
+                            pair.gen_floor = to_floor
+                        }
+                    }
+                }
+            }
+        }
+
+        State {
+            moves: self.moves + 1,
+            elevator_floor: to_floor,
+            pairs: new_pairs
+        }
+    }
+
+    fn is_floor_safe(&self, floor_index: usize) -> bool {
+        let mut any_gen = false;
+        let mut lone_chip = false;
+
+        for i in 0..self.pairs.len() {
+            let pair = self.pairs[i];
+            if pair.gen_floor == floor_index {
+                any_gen = true;
+            } else if pair.chip_floor == floor_index {
+                lone_chip = true;
+            }
+        }
+
+        // Any generator will fry a chip which isn't protected by its own generator
+        !(any_gen && lone_chip)
+    }
+
+    fn try_and_push(&self, item1: Item, item2opt : Option<Item>, queue: &mut UniqueQueue) -> Option<State> {
+        if item1.floor() != self.elevator_floor {
+            return None;
+        }
+        if let Some(ref item2) = item2opt {
+            if item2.floor() != self.elevator_floor {
+                return None;
+            }
+        }
+
+        let can_go_up = self.elevator_floor < NUM_FLOORS - 1;
+        let can_go_down = self.elevator_floor > 0;
+
+        if can_go_up {
+            let new_floor = self.elevator_floor + 1;
+            let new_state = self.move_items(&item1, &item2opt, new_floor);
+            if new_state.is_end_state() {
+                return Some(new_state)
+            }
+            if new_state.is_floor_safe(new_floor) && new_state.is_floor_safe(self.elevator_floor) {
+                queue.try_add(new_state);
+            }
+        }
+        if can_go_down {
+            let new_floor = self.elevator_floor - 1;
+            let new_state = self.move_items(&item1, &item2opt, new_floor);
+            if new_state.is_end_state() {
+                return Some(new_state)
+            }
+            if new_state.is_floor_safe(new_floor) && new_state.is_floor_safe(self.elevator_floor) {
+                queue.try_add(new_state);
+            }
+        }
+
+        None
+    }
+
+    fn push_next_possible_states(&self, queue: &mut UniqueQueue) -> Option<State> {
+        for i1 in 0..self.pairs.len() {
+            let pair1 = self.pairs[i1];
+            if let Some(s) = self.try_and_push(Item { pair: pair1, c_type: ComponentType::Generator }, None, queue) {
+                return Some(s)
+            }
+            if let Some(s) = self.try_and_push(Item { pair: pair1, c_type: ComponentType::Microchip }, None, queue) {
+                return Some(s)
+            }
+            // A generator can only share the elevator with its own microchip
+            if let Some(s) = self.try_and_push(Item { pair: pair1, c_type: ComponentType::Generator }, Some(Item { pair: pair1, c_type: ComponentType::Microchip }), queue) {
+                return Some(s)
+            }
+            for i2 in i1+1..self.pairs.len() {
+                let pair2 = self.pairs[i2];
+
+                if let Some(s) = self.try_and_push(Item { pair: pair1, c_type: ComponentType::Generator }, Some(Item { pair: pair2, c_type: ComponentType::Generator }), queue) {
+                    return Some(s)
+                }
+                if let Some(s) = self.try_and_push(Item { pair: pair1, c_type: ComponentType::Microchip }, Some(Item { pair: pair2, c_type: ComponentType::Microchip }), queue) {
+                    return Some(s)
+                }
+            }
+        }
+
+        None
+    }
+}
+
+struct UniqueQueue {
+    queue: VecDeque<State>,
+    history: HashSet<[u32;NUM_HASH_PARTS]>
+}
+
+impl UniqueQueue {
+    fn new() -> UniqueQueue {
+        UniqueQueue {
+            queue: VecDeque::new(),
+            history: HashSet::new()
+        }
+    }
+
+    fn try_add(&mut self, state: State) {
+        if self.history.insert(state.hash_counts()) {
+//            println!("New state: {}", state.hash());
+//            state.explain_hash();
+//            state.print();
+//            println!();
+            self.queue.push_back(state);
+        }
+//            else {
+//            println!("Redundant state: {}", state.hash());
+//            state.explain_hash();
+//            state.print();
+//            println!();
+//        }
+    }
+
+    fn unshift(&mut self) -> Option<State> {
+        self.queue.pop_front()
+    }
+}
+
+// An element's name, the floors its generator and microchip are on, and the line and text where it
+// was first mentioned, to point at if it's missing one of them
+struct Element<'a> {
+    name: &'a str,
+    gen_floor: Option<usize>,
+    chip_floor: Option<usize>,
+    line_num: usize,
+    line: &'a str
+}
+
+// Labels an element by the first letter of its name not already used by another element, or failing
+// that any letter which isn't used
+fn label(name: &str, pairs: &[Pair]) -> Option<char> {
+    name.to_uppercase().chars().chain('A'..='Z')
+        .find(|&c| pairs.iter().all(|p| p.element != c))
+}
+
+fn parse_pairs(input: &str) -> Result<Vec<Pair>, ParseError> {
+    let floor_re = Regex::new(r"The (?P<floor>first|second|third|fourth) floor").unwrap();
+    let generator_re = Regex::new(r"(?P<element>\w+) generator").unwrap();
+    let microchip_re = Regex::new(r"(?P<element>\w+)-compatible microchip").unwrap();
+
+    let mut elements:Vec<Element> = vec![];
+
+    for (i, line) in input.lines().enumerate() {
+        let line = line.trim_end();
+        let floor = match floor_re.captures(line).and_then(|caps| caps.name("floor")) {
+            Some("first") => 0,
+            Some("second") => 1,
+            Some("third") => 2,
+            Some("fourth") => 3,
+            _ => return Err(ParseError::new(i + 1, 1, line, "Expected a line describing the first, second, third or fourth floor"))
+        };
+
+        for caps in generator_re.captures_iter(line) {
+            let name = caps.name("element").unwrap();
+            match elements.iter().position(|e| e.name == name) {
+                Some(e) => elements[e].gen_floor = Some(floor),
+                None => elements.push(Element { name: name, gen_floor: Some(floor), chip_floor: None, line_num: i + 1, line: line })
+            }
+        }
+        for caps in microchip_re.captures_iter(line) {
+            let name = caps.name("element").unwrap();
+            match elements.iter().position(|e| e.name == name) {
+                Some(e) => elements[e].chip_floor = Some(floor),
+                None => elements.push(Element { name: name, gen_floor: None, chip_floor: Some(floor), line_num: i + 1, line: line })
+            }
+        }
+    }
+
+    let mut pairs:Vec<Pair> = vec![];
+    for element in elements {
+        let label = match label(element.name, &pairs) {
+            Some(label) => label,
+            None => return Err(ParseError::at(element.line_num, element.line, element.name, "Could not find a unique label for the element"))
+        };
+        let (gen_floor, chip_floor) = match (element.gen_floor, element.chip_floor) {
+            (Some(gen_floor), Some(chip_floor)) => (gen_floor, chip_floor),
+            (None, _) => return Err(ParseError::at(element.line_num, element.line, element.name, "No generator for the element")),
+            (_, None) => return Err(ParseError::at(element.line_num, element.line, element.name, "No microchip for the element"))
+        };
+        pairs.push(Pair::new(label, gen_floor, chip_floor));
+    }
+
+    Ok(pairs)
+}
+
+// Returns the minimum number of moves to bring all items to the top floor
+fn search(pairs: Vec<Pair>) -> usize {
+    let mut queue = UniqueQueue::new();
+
+    let init_state = State::new(0, pairs);
+    if init_state.is_end_state() {
+        return 0;
+    }
+
+    queue.try_add(init_state);
+
+    while let Some(state) = queue.unshift() {
+        if let Some(final_state) = state.push_next_possible_states(&mut queue) {
+            return final_state.moves;
+        }
+    }
+
+    panic!("Could not find a way to move all items to the top floor");
+}
+
+// Adds part 2's elerium and dilithium pairs to the first floor. They're labelled the same way as
+// the parsed elements, as pairs are moved by their label and two sharing one would move together.
+fn with_extra_pairs(pairs: Vec<Pair>) -> Vec<Pair> {
+    let mut pairs = pairs;
+    for name in &["elerium", "dilithium"] {
+        // There are far fewer elements than letters
+        let label = label(name, &pairs).unwrap();
+        pairs.push(Pair::new(label, 0, 0));
+    }
+    pairs
+}
+
+pub fn solve(input: &str) -> Result<(usize, usize), ParseError> {
+    let pairs = parse_pairs(input)?;
+
+    Ok((search(pairs.clone()), search(with_extra_pairs(pairs))))
+}
+
+#[test]
+fn example_takes_11_moves() {
+    assert_eq!(search(parse_pairs(include_str!("input-test.txt")).unwrap()), 11);
+}
+
+#[test]
+fn paired_generator_still_fries_another_chip() {
+    // The lithium generator has its own chip with it, but the hydrogen chip is unprotected
+    let state = State::new(0, vec![Pair::new('H', 0, 1), Pair::new('L', 1, 1)]);
+    assert!(!state.is_floor_safe(1));
+    assert!(state.is_floor_safe(0));
+}
+
+#[test]
+fn solves_puzzle_input() {
+    assert_eq!(solve(include_str!("input.txt")), Ok((37, 61)));
+}
+
+#[test]
+fn unpaired_items_and_unknown_lines_are_reported() {
+    let input = "The first floor contains a hydrogen-compatible microchip.\n\
+                 The second floor contains a hydrogen generator and a lithium generator.";
+    assert_eq!(solve(input), Err(ParseError::new(2, 54, "lithium", "No microchip for the element")));
+    assert_eq!(solve("The fifth floor contains nothing relevant."),
+               Err(ParseError::new(1, 1, "The fifth floor contains nothing relevant.",
+                                   "Expected a line describing the first, second, third or fourth floor")));
+}
+
+#[test]
+fn extra_pairs_get_labels_of_their_own() {
+    let input = "The first floor contains a europium generator and a europium-compatible microchip.\n\
+                 The second floor contains a dysprosium generator and a dysprosium-compatible microchip.";
+    let pairs = with_extra_pairs(parse_pairs(input).unwrap());
+    let labels:Vec<char> = pairs.iter().map(|p| p.element).collect();
+    assert_eq!(labels, vec!['E', 'D', 'L', 'I']);
+}
@@ -0,0 +1,40 @@
+extern crate assembunny;
+
+use assembunny::{Cpu, Instruction, Overflow, RunError, parse};
+
+// Returns the value left in register a with c initialised to 0, and with c initialised to 1
+pub fn solve(file: &str) -> Result<(i64, i64), RunError> {
+    let instructions = parse(file)?;
+
+    Ok((run(&instructions, 0)?, run(&instructions, 1)?))
+}
+
+fn run(instructions: &[Instruction], c_val: i64) -> Result<i64, Overflow> {
+    let mut cpu = Cpu::new(&[0, 0, c_val, 0]);
+    cpu.set_compiled(true);
+    cpu.process(instructions.to_vec())?;
+    Ok(cpu.regs[0])
+}
+
+#[test]
+fn example_program_leaves_42_in_a() {
+    let file = r"cpy 41 a
+inc a
+inc a
+dec a
+jnz a 2
+dec a";
+    assert_eq!(run(&parse(file).unwrap(), 0), Ok(42));
+}
+
+#[test]
+fn overflowing_register_is_reported_by_solve() {
+    let file = r"cpy 9223372036854775807 a
+inc a";
+    assert_eq!(solve(file), Err(RunError::Overflow(Overflow { pc: 1, instruction: "inc a".to_string() })));
+}
+
+#[test]
+fn solves_puzzle_input() {
+    assert_eq!(solve(include_str!("input.txt")), Ok((318083, 9227737)));
+}
@@ -1,8 +1,6 @@
 use std::collections::HashMap;
 use std::collections::VecDeque;
 
-const FAV_NUM:usize = 1358;
-
 struct Node {
     x: usize,
     y: usize,
@@ -12,24 +10,24 @@ struct Node {
 }
 
 impl Node {
-    fn new(x: usize, y: usize) -> Node {
+    fn new(x: usize, y: usize, fav_num: usize) -> Node {
         Node {
             x: x,
             y: y,
-            is_wall: Self::is_wall(x, y),
-            steps: std::usize::MAX,
+            is_wall: Self::is_wall(x, y, fav_num),
+            steps: usize::MAX,
             prev: None
         }
     }
 
-    fn is_wall(x: usize, y: usize) -> bool {
-        let mut num = (x*x + 3*x + 2*x*y + y + y*y) + FAV_NUM;
+    fn is_wall(x: usize, y: usize, fav_num: usize) -> bool {
+        let mut num = (x*x + 3*x + 2*x*y + y + y*y) + fav_num;
         let mut bit_count = 0;
         while num > 0 {
             if num % 2 == 1 {
                 bit_count += 1;
             }
-            num = num / 2;
+            num /= 2;
         }
         bit_count % 2 == 1
     }
@@ -70,14 +68,18 @@ struct SearchState {
 
 struct Searcher {
     nodes: HashMap<(usize, usize), Node>,
-    queue: VecDeque<(usize, usize)>
+    queue: VecDeque<(usize, usize)>,
+    fav_num: usize,
+    target_steps: Option<usize>
 }
 
 impl Searcher {
-    fn new(mut start_node: Node) -> Searcher {
+    fn new(mut start_node: Node, fav_num: usize) -> Searcher {
         let mut searcher = Searcher {
             nodes: HashMap::new(),
-            queue: VecDeque::new()
+            queue: VecDeque::new(),
+            fav_num: fav_num,
+            target_steps: None
         };
 
         start_node.steps = 0;
@@ -98,16 +100,14 @@ impl Searcher {
     }
 
     fn get_or_create(&mut self, (x, y):(usize, usize)) -> &mut Node {
-        self.nodes.entry((x, y)).or_insert(Node::new(x, y))
+        let fav_num = self.fav_num;
+        self.nodes.entry((x, y)).or_insert_with(|| Node::new(x, y, fav_num))
     }
 
-    fn search(&mut self, target:(usize, usize), max_steps: usize) {
-        loop {
-            let (coord, current_steps) = match self.pop() {
-                Some(t) => t,
-                None => break
-            };
-
+    // Returns the number of steps to reach the target, and the number of nodes reachable in at most
+    // max_steps steps
+    fn search(&mut self, target:(usize, usize), max_steps: usize) -> (Option<usize>, usize) {
+        while let Some((coord, current_steps)) = self.pop() {
             let mut state = SearchState {
                 target: target,
                 max_steps: max_steps,
@@ -122,7 +122,8 @@ impl Searcher {
         }
 
         let count = self.nodes.iter().filter(|&(_, node)| node.steps <= max_steps).count();
-        println!("{} nodes reachable in <= {} steps", count, max_steps);
+
+        (self.target_steps, count)
     }
 
     fn get_neighbour_coords_to_process(&mut self, state: &mut SearchState) -> Vec<(usize, usize)> {
@@ -131,13 +132,12 @@ impl Searcher {
             node.neighbour_coords()
         };
 
-        neighbour_coords.into_iter()
+        let result = neighbour_coords.into_iter()
             .filter(|& neighbour_coord| {
-                let mut neighbour_node = self.get_or_create(neighbour_coord);
+                let neighbour_node = self.get_or_create(neighbour_coord);
 
                 if neighbour_node.update(state.current_coords, state.current_steps) {
                     if state.target == neighbour_coord {
-                        println!("Reached {:?} in {} steps", state.target, neighbour_node.steps);
                         state.found_target = true;
                     }
 
@@ -148,11 +148,32 @@ impl Searcher {
 
                 return false;
             })
-            .collect()
+            .collect();
+
+        if state.found_target && self.target_steps.is_none() {
+            self.target_steps = self.nodes.get(&state.target).map(|node| node.steps);
+        }
+
+        result
     }
 }
 
-fn main() {
-    let mut searcher = Searcher::new(Node::new(1, 1));
-    searcher.search((31, 39), 50);
+pub fn solve(input: &str) -> (usize, usize) {
+    let fav_num = input.trim().parse::<usize>().unwrap();
+
+    let mut searcher = Searcher::new(Node::new(1, 1, fav_num), fav_num);
+    let (target_steps, reachable_count) = searcher.search((31, 39), 50);
+
+    (target_steps.expect("Could not reach (31, 39)"), reachable_count)
+}
+
+#[test]
+fn example_reaches_7_4_in_11_steps() {
+    let mut searcher = Searcher::new(Node::new(1, 1, 10), 10);
+    assert_eq!(searcher.search((7, 4), 11).0, Some(11));
+}
+
+#[test]
+fn solves_puzzle_input() {
+    assert_eq!(solve(include_str!("input.txt")), (96, 141));
 }
@@ -5,16 +5,15 @@ use crypto::digest::Digest;
 
 use std::collections::HashMap;
 
-fn get_64th_key_index(repeat_hashes: usize) -> usize {
-    let salt = b"jlmsuwbz";
+fn get_64th_key_index(salt: &str, repeat_hashes: usize) -> usize {
     let mut md5 = Md5::new();
 
     let mut triple_indexes_by_char:HashMap<u8, Vec<usize>> = HashMap::new();
 
     let mut key_count = 0;
 
-    for hash_counter in 0..std::usize::MAX {
-        md5.input(salt);
+    for hash_counter in 0..usize::MAX {
+        md5.input(salt.as_bytes());
         md5.input(hash_counter.to_string().as_bytes());
 
         let mut result = md5.result_str();
@@ -34,18 +33,18 @@ fn get_64th_key_index(repeat_hashes: usize) -> usize {
         }
 
         for idx in 0..30 {
-            let a = nibbles[idx + 0];
+            let a = nibbles[idx];
             let b = nibbles[idx + 1];
             let c = nibbles[idx + 2];
             if (a == b) && (b == c) {
-                let mut vec = triple_indexes_by_char.entry(a).or_insert(vec![]);
+                let vec = triple_indexes_by_char.entry(a).or_insert(vec![]);
                 vec.push(hash_counter);
                 break; // only consider first triple in a hash
             }
         }
         // TODO: Only look for quintuple if found a triple
         for idx in 0..28 {
-            let a = nibbles[idx + 0];
+            let a = nibbles[idx];
             let b = nibbles[idx + 1];
             let c = nibbles[idx + 2];
             let d = nibbles[idx + 3];
@@ -75,7 +74,18 @@ fn get_64th_key_index(repeat_hashes: usize) -> usize {
     0
 }
 
-fn main() {
-    println!("Part 1: {}", get_64th_key_index(0));
-    println!("Part 2: {}", get_64th_key_index(2016));
+pub fn solve(input: &str) -> (usize, usize) {
+    let salt = input.trim();
+    (get_64th_key_index(salt, 0), get_64th_key_index(salt, 2016))
+}
+
+#[test]
+fn abc_salt_gives_64th_key_at_22728() {
+    assert_eq!(get_64th_key_index("abc", 0), 22728);
+}
+
+#[test]
+#[ignore] // Stretched hashing takes minutes without optimisations
+fn solves_puzzle_input() {
+    assert_eq!(solve(include_str!("input.txt")), (35186, 22429));
 }
@@ -0,0 +1,86 @@
+extern crate regex;
+use regex::Regex;
+
+struct Disc {
+    positions: usize,
+    delayed_start_pos: usize
+}
+
+impl Disc {
+    fn new(positions: usize, start_pos:usize, drop_delay:usize) -> Disc {
+        Disc {
+            positions: positions,
+            delayed_start_pos: start_pos + drop_delay
+        }
+    }
+
+    fn pos_at_time(&self, time: usize) -> usize {
+        (self.delayed_start_pos + time) % self.positions
+    }
+
+    fn combine_with(&self, other:Disc) -> Disc {
+        Disc {
+            positions: self.positions * other.positions,
+            delayed_start_pos: 0
+        }
+    }
+}
+
+fn parse_discs(input: &str) -> Vec<Disc> {
+    let disc_re = Regex::new(
+        r"Disc #(?P<num>\d+) has (?P<positions>\d+) positions; at time=0, it is at position (?P<start>\d+)\."
+    ).unwrap();
+
+    input.lines().map(|line| {
+        let caps = disc_re.captures(line).unwrap_or_else(|| panic!("Unrecognised line: {}", line));
+        Disc::new(
+            caps.name("positions").unwrap().parse::<usize>().unwrap(),
+            caps.name("start").unwrap().parse::<usize>().unwrap(),
+            caps.name("num").unwrap().parse::<usize>().unwrap()
+        )
+    }).collect()
+}
+
+fn find_time(mut discs: Vec<Disc>) -> usize {
+    let mut cur_discs = discs.remove(0);
+
+    let mut time = cur_discs.positions - cur_discs.delayed_start_pos; // start with 1st disc as pos 0
+
+    while !discs.is_empty() {
+        let mut delta = 0;
+        let next_disc = discs.remove(0);
+        loop {
+            if next_disc.pos_at_time(time + delta) == 0 {
+                time += delta;
+                cur_discs = cur_discs.combine_with(next_disc);
+                break;
+            } else {
+                delta += cur_discs.positions;
+            }
+        }
+    }
+
+    time
+}
+
+pub fn solve(input: &str) -> (usize, usize) {
+    let discs = parse_discs(input);
+
+    let mut extra_discs = parse_discs(input);
+    let extra_delay = extra_discs.len() + 1;
+    extra_discs.push(Disc::new(11, 0, extra_delay));
+
+    (find_time(discs), find_time(extra_discs))
+}
+
+#[test]
+fn example_discs_align_at_time_5() {
+    let input = r"Disc #1 has 5 positions; at time=0, it is at position 4.
+Disc #2 has 2 positions; at time=0, it is at position 1.";
+    assert_eq!(find_time(parse_discs(input)), 5);
+}
+
+#[test]
+fn solves_puzzle_input() {
+    assert_eq!(solve(include_str!("input.txt")), (376777, 3903937));
+}
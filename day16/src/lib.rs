@@ -1,7 +1,6 @@
-fn checksum(disk_size:usize) -> String {
-    let seed = b"00101000101111010";
+fn checksum(seed: &str, disk_size:usize) -> String {
     let mut data = vec![];
-    for b in seed.iter() {
+    for b in seed.as_bytes().iter() {
         data.push(if *b == 49 { 1 } else { 0 });
     }
 
@@ -32,7 +31,17 @@ fn checksum(disk_size:usize) -> String {
     }
 }
 
-fn main() {
-    println!("Part 1 checksum: {}", checksum(272));
-    println!("Part 2 checksum: {}", checksum(35651584));
+pub fn solve(input: &str) -> (String, String) {
+    let seed = input.trim();
+    (checksum(seed, 272), checksum(seed, 35651584))
+}
+
+#[test]
+fn example_seed_fills_20_to_checksum_01100() {
+    assert_eq!(checksum("10000", 20), "01100");
+}
+
+#[test]
+fn solves_puzzle_input() {
+    assert_eq!(solve(include_str!("input.txt")), ("10010100110011100".to_string(), "01100100101101100".to_string()));
 }
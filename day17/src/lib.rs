@@ -74,21 +74,18 @@ fn get_unlocked_doors(path: &str, position: &Position) -> Vec<Dir> {
 
     let mut dirs = vec![];
     for (i, c) in hash.chars().enumerate() {
-        match c {
-            'b'...'f' => {
-                let dir = Dir::from_index(i);
-                if position.can_go(&dir) {
-                    dirs.push(dir);
-                }
-            },
-            _ => {}
+        if let 'b'..='f' = c {
+            let dir = Dir::from_index(i);
+            if position.can_go(&dir) {
+                dirs.push(dir);
+            }
         }
     }
 
     dirs
 }
 
-fn solve(passcode: String, position: Position) -> Option<(String, usize)> {
+fn find_paths(passcode: String, position: Position) -> Option<(String, usize)> {
     let mut queue = VecDeque::new();
     queue.push_back((passcode.clone(), position));
 
@@ -114,20 +111,20 @@ fn solve(passcode: String, position: Position) -> Option<(String, usize)> {
     shortest.and_then(|s| longest.map(|l| (s, l)))
 }
 
-fn main() {
-    let (shortest_path, longest_length) = solve("pvhmgsws".to_string(), Position::new(0,0)).unwrap();
-    println!("Shortest path: {}, longest length: {}", shortest_path, longest_length);
+// Returns the shortest path to the vault, and the length of the longest path
+pub fn solve(passcode: &str) -> (String, usize) {
+    find_paths(passcode.trim().to_string(), Position::new(0,0)).expect("No path to the vault")
 }
 
 #[test]
 fn hijkl_has_no_solutions() {
-    assert_eq!(solve("hijkl".to_string(), Position::new(0,0)), None);
+    assert_eq!(find_paths("hijkl".to_string(), Position::new(0,0)), None);
 }
 
 #[test]
 fn ihgpwlah() {
     assert_eq!(
-    solve("ihgpwlah".to_string(), Position::new(0,0)),
+    find_paths("ihgpwlah".to_string(), Position::new(0,0)),
     Some(("DDRRRD".to_string(), 370))
     );
 }
@@ -135,7 +132,7 @@ fn ihgpwlah() {
 #[test]
 fn kglvqrro() {
     assert_eq!(
-    solve("kglvqrro".to_string(), Position::new(0,0)),
+    find_paths("kglvqrro".to_string(), Position::new(0,0)),
     Some(("DDUDRLRRUDRD".to_string(), 492))
     );
 }
@@ -143,7 +140,12 @@ fn kglvqrro() {
 #[test]
 fn ulqzkmiv() {
     assert_eq!(
-    solve("ulqzkmiv".to_string(), Position::new(0,0)),
+    find_paths("ulqzkmiv".to_string(), Position::new(0,0)),
     Some(("DRURDRUDDLLDLUURRDULRLDUUDDDRR".to_string(), 830))
     );
 }
+
+#[test]
+fn solves_puzzle_input() {
+    assert_eq!(solve(include_str!("input.txt")), ("DRRDRLDURD".to_string(), 618));
+}
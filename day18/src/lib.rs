@@ -31,6 +31,8 @@ impl Floor {
                 let centre = prev_row[i];
                 let right = if i < prev_row.len() - 1 { prev_row[i + 1] } else { false };
 
+                // The four trap rules, as given in the puzzle
+                #[allow(clippy::nonminimal_bool)]
                 let is_trap = (left && centre && !right) ||
                     (!left && centre && right) ||
                     (left && !centre && !right) ||
@@ -52,6 +54,7 @@ impl Floor {
         }
     }
 
+    #[cfg(test)]
     fn as_string(&self) -> String {
         self.tiles.iter().map(|row| {
             row.iter()
@@ -68,13 +71,13 @@ impl Floor {
     }
 }
 
-fn main() {
-    let mut floor = Floor::new(".^^^^^.^^.^^^.^...^..^^.^.^..^^^^^^^^^^..^...^^.^..^^^^..^^^^...^.^.^^^^^^^^....^..^^^^^^.^^^.^^^.^^");
+pub fn solve(first_row: &str) -> (usize, usize) {
+    let mut floor = Floor::new(first_row.trim());
     floor.gen_rows(39);
-    println!("Part 1: {}", floor.num_safe_tiles());
+    let part1 = floor.num_safe_tiles();
 
     floor.gen_rows(400000 - 40);
-    println!("Part 2: {}", floor.num_safe_tiles());
+    (part1, floor.num_safe_tiles())
 }
 
 #[test]
@@ -103,3 +106,8 @@ fn ten_line_example() {
 ^^.^^^..^^");
     assert_eq!(floor.num_safe_tiles(), 38);
 }
+
+#[test]
+fn solves_puzzle_input() {
+    assert_eq!(solve(include_str!("input.txt")), (1989, 19999894));
+}
@@ -0,0 +1,130 @@
+pub fn naive_part2(num_elves: usize) -> usize {
+    let mut elves = vec![];
+    for i in 0..num_elves {
+        elves.push(i+1);
+    }
+
+    let mut current = 0;
+    while elves.len() > 1 {
+//        println!("{}:{}, {:?}", current, elves[current], elves);
+        let to_kill = ((elves.len() / 2) + current) % elves.len();
+        elves.remove(to_kill);
+        if current < to_kill { current += 1}
+        current %= elves.len();
+    }
+
+    elves[0]
+}
+
+pub struct ElfCircle {
+    elves: Vec<bool>,
+    size: usize,
+    to_remove: usize
+}
+
+impl ElfCircle {
+    pub fn new(num_elves: usize) -> ElfCircle {
+        ElfCircle {
+            elves: vec![true; num_elves],
+            size: num_elves,
+            to_remove: num_elves / 2
+        }
+    }
+
+    fn remove_opposite(&mut self) {
+        self.elves[self.to_remove] = false;
+
+        let skip = if self.size.is_multiple_of(2) { 1 } else { 2 };
+
+        self.size -= 1;
+
+        for _ in 0..skip {
+            self.move_on();
+        }
+    }
+
+    fn move_on(&mut self) {
+        self.to_remove = (self.to_remove + 1) % self.elves.len();
+        while !self.elves[self.to_remove] {
+            self.to_remove = (self.to_remove + 1) % self.elves.len();
+        }
+    }
+
+    pub fn remove_all_but_one(&mut self) -> usize {
+        while self.size > 1 {
+            self.remove_opposite();
+        }
+
+        self.elves.iter().position(|&b| b).unwrap() + 1
+    }
+}
+
+// The survivor for a given number of elves is a series that follows a pattern, wrapping around at
+// each power of three:
+// - each n from 3^x to 2 * 3^x (inclusive) => n - 3^x
+// - each n from 2 * 3^x + 1 to 3^(x+1) - 1 => (n - 3^x) + (n - 3^x - 3^x)
+// so it goes:
+//  1 => 1
+//
+//  2 => 1
+//  3 => 2 + 1
+//
+//  4 => 1
+//  5 => 2
+//  6 => 3
+//  7 => 4 + 1
+//  8 => 5 + 2
+//  9 => 6 + 3
+pub fn find_survivor_by_pattern(num_elves: usize) -> usize {
+    if num_elves == 1 {
+        return 1;
+    }
+
+    let mut biggest_smaller_power = 1;
+    while biggest_smaller_power*3 < num_elves {
+        biggest_smaller_power *= 3;
+    }
+
+    if num_elves <= 2 * biggest_smaller_power {
+        num_elves - biggest_smaller_power
+    } else {
+        (num_elves - biggest_smaller_power) + (num_elves - 2 * biggest_smaller_power)
+    }
+}
+
+// Josephus problem: subtract highest power of 2, double, add one.
+pub fn find_survivor_stealing_from_left(num_elves: usize) -> usize {
+    let mut biggest_power = 1;
+    while biggest_power * 2 <= num_elves {
+        biggest_power *= 2;
+    }
+    2 * (num_elves - biggest_power) + 1
+}
+
+pub fn solve(input: &str) -> (usize, usize) {
+    let num_elves = input.trim().parse::<usize>().unwrap();
+
+    // Part 2 was originally solved naively, by simulating removing elves from a vec (naive_part2),
+    // then by simulating with an array of bools (ElfCircle). Even better (having read around):
+    // numerically calculate survivor based on pattern
+    (find_survivor_stealing_from_left(num_elves), find_survivor_by_pattern(num_elves))
+}
+
+#[test]
+fn five_elves_stealing_from_left_leaves_elf_3() {
+    assert_eq!(find_survivor_stealing_from_left(5), 3);
+}
+
+#[test]
+fn pattern_matches_simulations() {
+    for num_elves in 1..100 {
+        let expected = naive_part2(num_elves);
+        assert_eq!(ElfCircle::new(num_elves).remove_all_but_one(), expected, "ElfCircle of {}", num_elves);
+        assert_eq!(find_survivor_by_pattern(num_elves), expected, "Pattern for {}", num_elves);
+    }
+}
+
+#[test]
+fn solves_puzzle_input() {
+    assert_eq!(solve(include_str!("input.txt")), (1816277, 1410967));
+}
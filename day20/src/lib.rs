@@ -1,16 +1,3 @@
-fn part1(input: &mut Vec<(usize, usize)>) {
-    let mut lowest = 0;
-    for &mut (from, to) in input {
-        if from <= lowest {
-            lowest = std::cmp::max(lowest, to + 1);
-        } else {
-            break;
-        }
-    }
-
-    println!("{}", lowest);
-}
-
 fn get_available_ranges(input: &mut Vec<(usize, usize)>, max: usize) -> Vec<(usize, usize)> {
     let mut available = vec![];
 
@@ -29,8 +16,9 @@ fn get_available_ranges(input: &mut Vec<(usize, usize)>, max: usize) -> Vec<(usi
     available
 }
 
-fn main() {
-    let mut input:Vec<(usize, usize)> = include_str!("input.txt").lines()
+// Returns the lowest available IP, and the number of available IPs
+pub fn solve(file: &str) -> (usize, usize) {
+    let mut input:Vec<(usize, usize)> = file.lines()
         .map(|l| {
             let nums:Vec<usize> = l.split("-")
                 .map(|p| p.parse::<usize>().unwrap())
@@ -38,12 +26,23 @@ fn main() {
             (nums[0], nums[1])
         })
         .collect();
-    input.sort_by(|&(a, _), &(c, _)| a.cmp(&c));
+    input.sort_by_key(|&(a, _)| a);
 
     let available = get_available_ranges(&mut input, 4294967295);
-    println!("Lowest available IP: {}", available[0].0);
 
-    let sum:usize = available.into_iter().map(|(from, to)| to + 1 - from).sum();
-    println!("Sum of available ranges: {}", sum);
+    let sum:usize = available.iter().map(|&(from, to)| to + 1 - from).sum();
+
+    (available[0].0, sum)
+}
+
+#[test]
+fn example_blocklist_allows_3_first_and_2_total() {
+    let mut input = vec![(0, 2), (4, 7), (5, 8)];
+    let available = get_available_ranges(&mut input, 9);
+    assert_eq!(available, vec![(3, 3), (9, 9)]);
+}
 
+#[test]
+fn solves_puzzle_input() {
+    assert_eq!(solve(include_str!("input.txt")), (23923783, 125));
 }
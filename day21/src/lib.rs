@@ -21,9 +21,7 @@ fn scramble(plaintext: &str, file: &str) -> String {
             // from 0) should be swapped.
             let x = caps.name("x").unwrap().parse::<usize>().unwrap();
             let y = caps.name("y").unwrap().parse::<usize>().unwrap();
-            let tmp = chars[x];
-            chars[x] = chars[y];
-            chars[y] = tmp;
+            chars.swap(x, y);
         } else if let Some(caps) = swap_letter_re.captures(line) {
             //swap letter X with letter Y means that the letters X and Y should be swapped
             // (regardless of where they appear in the string).
@@ -72,9 +70,7 @@ fn scramble(plaintext: &str, file: &str) -> String {
             let x = caps.name("x").unwrap().parse::<usize>().unwrap();
             let y = caps.name("y").unwrap().parse::<usize>().unwrap();
             for i in 0..(y+1-x)/2 {
-                let tmp = chars[x+i];
-                chars[x+i] = chars[y-i];
-                chars[y-i] = tmp;
+                chars.swap(x+i, y-i);
             }
         } else if let Some(caps) = move_pos_re.captures(line) {
             //move position X to position Y means that the letter which is at index X should be
@@ -103,7 +99,7 @@ fn unscramble(plaintext: &str, file: &str) -> String {
 
     // Pre-calculate inverse lookup of rotate positions
     let mut rotate_inverse_lookup = HashMap::new();
-    let positions = (0..plaintext.len()).into_iter()
+    let positions = (0..plaintext.len())
         .map(|i| (i + i + if i >=4 { 2 } else { 1 }) % plaintext.len());
     for (i, pos) in positions.enumerate() {
 //        println!("({}, {})", i, pos);
@@ -120,9 +116,7 @@ fn unscramble(plaintext: &str, file: &str) -> String {
             // from 0) should be swapped.
             let x = caps.name("x").unwrap().parse::<usize>().unwrap();
             let y = caps.name("y").unwrap().parse::<usize>().unwrap();
-            let tmp = chars[x];
-            chars[x] = chars[y];
-            chars[y] = tmp;
+            chars.swap(x, y);
         } else if let Some(caps) = swap_letter_re.captures(line) {
             //swap letter X with letter Y means that the letters X and Y should be swapped
             // (regardless of where they appear in the string).
@@ -175,9 +169,7 @@ fn unscramble(plaintext: &str, file: &str) -> String {
             let x = caps.name("x").unwrap().parse::<usize>().unwrap();
             let y = caps.name("y").unwrap().parse::<usize>().unwrap();
             for i in 0..(y+1-x)/2 {
-                let tmp = chars[x+i];
-                chars[x+i] = chars[y-i];
-                chars[y-i] = tmp;
+                chars.swap(x+i, y-i);
             }
         } else if let Some(caps) = move_pos_re.captures(line) {
             //move position X to position Y means that the letter which is at index X should be
@@ -196,9 +188,8 @@ fn unscramble(plaintext: &str, file: &str) -> String {
     chars.into_iter().collect()
 }
 
-fn main() {
-    println!("Part 1: {}", scramble("abcdefgh", include_str!("input.txt")));
-    println!("Part 2: {}", unscramble("fbgdceah", include_str!("input.txt")));
+pub fn solve(file: &str) -> (String, String) {
+    (scramble("abcdefgh", file), unscramble("fbgdceah", file))
 }
 
 #[test]
@@ -214,4 +205,9 @@ fn decab_test_unscrambles_to_abcde() {
 #[test]
 fn bfheacgd_unscrambles_to_abcdefgh() {
     assert_eq!("abcdefgh", unscramble("bfheacgd", include_str!("input.txt")))
-}
\ No newline at end of file
+}
+
+#[test]
+fn solves_puzzle_input() {
+    assert_eq!(solve(include_str!("input.txt")), ("bfheacgd".to_string(), "gcehdbfa".to_string()));
+}
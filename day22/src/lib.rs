@@ -1,29 +1,48 @@
 extern crate regex;
 use regex::Regex;
 
-#[macro_use] extern crate itertools;
+extern crate itertools;
 use itertools::Itertools;
 
-fn count_viable_pairs(file:&str) -> usize {
+extern crate parse_error;
+use parse_error::ParseError;
+
+// ((x, y), avail, used)
+type Node = ((usize, usize), usize, usize);
+
+// Reads each node from the df output, sorted by available space
+fn parse_nodes(file: &str) -> Result<Vec<Node>, ParseError> {
     let df_re = Regex::new(
-        r"dev/grid/node-x(?P<x>\d+)-y(?P<y>\d+)\s+(?P<size>\d+)T\s+(?P<used>\d+)T\s+(?P<avail>\d+)T\s+(?P<use>\d+)%"
+        r"^/dev/grid/node-x(?P<x>\d+)-y(?P<y>\d+)\s+(?P<size>\d+)T\s+(?P<used>\d+)T\s+(?P<avail>\d+)T\s+(?P<use>\d+)%$"
     ).unwrap();
 
     let mut nodes = vec![];
-    for line in file.lines().skip(2) {
+    // The first two lines are the command and the column headings
+    for (i, line) in file.lines().enumerate().skip(2) {
+        let line = line.trim_end();
         if let Some(captures) = df_re.captures(line) {
-            let x = captures.name("x").unwrap().parse::<usize>().unwrap();
-            let y = captures.name("y").unwrap().parse::<usize>().unwrap();
-            let avail = captures.name("avail").unwrap().parse::<usize>().unwrap();
-            let used = captures.name("used").unwrap().parse::<usize>().unwrap();
+            let number = |name| {
+                let text = captures.name(name).unwrap();
+                text.parse::<usize>().map_err(|_| ParseError::at(i + 1, line, text, "Number is too large"))
+            };
+            let x = number("x")?;
+            let y = number("y")?;
+            let avail = number("avail")?;
+            let used = number("used")?;
             nodes.push(((x,y), avail, used));
         } else {
-            unreachable!("Line in unexpected format: {}", line);
+            return Err(ParseError::new(i + 1, 1, line, "Line in unexpected format"));
         }
     }
     nodes.sort_by_key(|&(_, avail, _)| avail);
 
-    nodes.iter()
+    Ok(nodes)
+}
+
+fn count_viable_pairs(file:&str) -> Result<usize, ParseError> {
+    let nodes = parse_nodes(file)?;
+
+    Ok(nodes.iter()
         .filter(|&&(_, _, used)| used > 0)
         .map(|&(_, _, used)| {
         let mut index = match nodes.binary_search_by_key(&used, |&(_, avail, _)| avail) {
@@ -35,7 +54,7 @@ fn count_viable_pairs(file:&str) -> usize {
         }
 
         nodes.len() - std::cmp::max(1, index)
-    }).sum()
+    }).sum())
 }
 
 #[derive(Debug)]
@@ -58,24 +77,8 @@ struct Board {
 }
 
 impl Board {
-    fn new(file: &str, size: usize) -> Board {
-        let df_re = Regex::new(
-            r"dev/grid/node-x(?P<x>\d+)-y(?P<y>\d+)\s+(?P<size>\d+)T\s+(?P<used>\d+)T\s+(?P<avail>\d+)T\s+(?P<use>\d+)%"
-        ).unwrap();
-
-        let mut nodes = vec![];
-        for line in file.lines().skip(2) {
-            if let Some(captures) = df_re.captures(line) {
-                let x = captures.name("x").unwrap().parse::<usize>().unwrap();
-                let y = captures.name("y").unwrap().parse::<usize>().unwrap();
-                let avail = captures.name("avail").unwrap().parse::<usize>().unwrap();
-                let used = captures.name("used").unwrap().parse::<usize>().unwrap();
-                nodes.push(((x,y), avail, used));
-            } else {
-                unreachable!("Line in unexpected format: {}", line);
-            }
-        }
-        nodes.sort_by_key(|&(_, avail, _)| avail);
+    fn new(file: &str, size: usize) -> Result<Board, ParseError> {
+        let nodes = parse_nodes(file)?;
 
         let mut pieces = nodes.iter()
             .map(|node| {
@@ -124,9 +127,9 @@ impl Board {
             })
             .collect();
 
-        Board {
+        Ok(Board {
             pieces: grouped_pieces
-        }
+        })
     }
 
     fn visualise(&self) -> String {
@@ -147,16 +150,15 @@ impl Board {
     }
 }
 
-fn main() {
-    let file = include_str!("input.txt");
-
-    println!("Viable pairs: {}", count_viable_pairs(file));
+// Returns the number of viable pairs, and a visualisation of the board from which part 2 can be
+// worked out by hand
+pub fn solve(file: &str) -> Result<(usize, String), ParseError> {
+    let max_x = parse_nodes(file)?.iter()
+        .map(|&((x, _), _, _)| x)
+        .max()
+        .unwrap_or(0);
 
-    println!("");
-
-    println!("Part 2 board:");
-    let board = Board::new(file, 35);
-    println!("{}", board.visualise());
+    let board = Board::new(file, max_x)?;
 
     // Worked out part 2 by hand from here, as it's clear that the board is just a horizontal wall
     // of fixed pieces in the middle, and the empty space two rows up from the bottom right.
@@ -168,6 +170,8 @@ fn main() {
     //  - moving the goal left now takes 5 moves (empty space down one, left three, up one, goal one left)
     //  - We need to move it 34 times: 170 moves [265 total]
     // Therefore a minimum total of 265 moves needed
+
+    Ok((count_viable_pairs(file)?, board.visualise()))
 }
 
 #[test]
@@ -180,7 +184,7 @@ Filesystem              Size  Used  Avail  Use%
 /dev/grid/node-x0-y3     89T   69T    20T   77%
 /dev/grid/node-x0-y4     88T   67T    21T   76%
 /dev/grid/node-x0-y5     87T   72T    15T   82%";
-    let pairs = count_viable_pairs(file);
+    let pairs = count_viable_pairs(file).unwrap();
     assert_eq!(5, pairs);
 }
 
@@ -194,7 +198,7 @@ Filesystem              Size  Used  Avail  Use%
 /dev/grid/node-x0-y3     89T   69T    20T   77%
 /dev/grid/node-x0-y4     88T   67T    21T   76%
 /dev/grid/node-x0-y5     87T   72T    15T   82%";
-    let pairs = count_viable_pairs(file);
+    let pairs = count_viable_pairs(file).unwrap();
     assert_eq!(2, pairs);
 }
 
@@ -208,7 +212,7 @@ Filesystem              Size  Used  Avail  Use%
 /dev/grid/node-x0-y3     89T   69T    20T   77%
 /dev/grid/node-x0-y4     88T   67T    21T   76%
 /dev/grid/node-x0-y5     87T   72T    15T   82%";
-    let pairs = count_viable_pairs(file);
+    let pairs = count_viable_pairs(file).unwrap();
     assert_eq!(0, pairs);
 }
 
@@ -219,5 +223,25 @@ fn example_df_gives_example_board() {
 ._.
 #..
 ";
-    assert_eq!(expected_board, Board::new(file, 2).visualise());
+    assert_eq!(expected_board, Board::new(file, 2).unwrap().visualise());
+}
+
+#[test]
+fn solve_finds_the_width_of_the_example_board() {
+    assert_eq!(solve(include_str!("input-test.txt")).unwrap().1, "..G\n._.\n#..\n");
+}
+
+#[test]
+fn solves_puzzle_input() {
+    assert_eq!(solve(include_str!("input.txt")).unwrap().0, 1045);
+}
+
+#[test]
+fn unexpected_line_is_reported() {
+    let file = r"root@ebhq-gridcenter# df -h
+Filesystem              Size  Used  Avail  Use%
+/dev/grid/node-x0-y0     91T   66T    25T   72%
+/dev/grid/node-x0-y1     87T   2T";
+    assert_eq!(count_viable_pairs(file),
+               Err(ParseError::new(4, 1, "/dev/grid/node-x0-y1     87T   2T", "Line in unexpected format")));
 }
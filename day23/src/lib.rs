@@ -0,0 +1,34 @@
+extern crate assembunny;
+
+use assembunny::{Cpu, Instruction, Overflow, RunError, parse};
+
+// Returns the value sent to the safe (left in register a) with 7 eggs, and with 12 eggs
+pub fn solve(file: &str) -> Result<(i64, i64), RunError> {
+    let instructions = parse(file)?;
+
+    Ok((run(instructions.clone(), 7)?, run(instructions, 12)?))
+}
+
+// 12! only just fits in 32 bits, so the registers are 64 bits and checked, in case of bigger inputs
+fn run(instructions: Vec<Instruction>, eggs: i64) -> Result<i64, Overflow> {
+    let mut cpu = Cpu::new(&[eggs, 0, 0, 0]);
+    cpu.process(instructions)?;
+    Ok(cpu.regs[0])
+}
+
+#[test]
+fn example_toggles_leave_3_in_a() {
+    assert_eq!(run(parse(include_str!("input-test.txt")).unwrap(), 0), Ok(3));
+}
+
+#[test]
+fn overflowing_register_is_reported_by_solve() {
+    let file = r"cpy 9223372036854775807 a
+inc a";
+    assert_eq!(solve(file), Err(RunError::Overflow(Overflow { pc: 1, instruction: "inc a".to_string() })));
+}
+
+#[test]
+fn solves_puzzle_input() {
+    assert_eq!(solve(include_str!("input.txt")), Ok((12624, 479009184)));
+}
@@ -0,0 +1,184 @@
+extern crate permutohedron;
+use permutohedron::Heap;
+
+use std::collections::VecDeque;
+use std::collections::{HashSet, HashMap};
+
+extern crate parse_error;
+use parse_error::ParseError;
+
+struct FloorPlan {
+    data: Vec<Vec<bool>>,
+    points_to_visit: Vec<(usize, usize)>,
+}
+
+impl FloorPlan {
+    fn new(file: &str) -> Result<FloorPlan, ParseError> {
+        let mut plan = vec![];
+        let mut points:Vec<Option<(usize, usize)>> = vec![];
+
+        for (row, line) in file.lines().enumerate() {
+            let mut plan_row = vec![];
+            for (col, char) in line.trim_end().chars().enumerate() {
+                match char {
+                    '#' => {
+                        plan_row.push(false);
+                    },
+                    '0'..='9' => {
+                        let point = char.to_digit(10).unwrap() as usize;
+                        if points.len() <= point {
+                            points.resize(point + 1, None);
+                        }
+                        if points[point].is_some() {
+                            return Err(ParseError::new(row + 1, col + 1, &char.to_string(), "Point appears more than once"));
+                        }
+                        points[point] = Some((row, col));
+                        plan_row.push(true);
+                    },
+                    '.' => {
+                        plan_row.push(true);
+                    },
+                    _ => {
+                        return Err(ParseError::new(row + 1, col + 1, &char.to_string(), "Expected '#', '.' or a digit"));
+                    }
+                }
+            }
+            plan.push(plan_row);
+        };
+
+        // A gap in the numbering can only be noticed once the whole plan has been read
+        let mut points_to_visit = vec![];
+        for (i, point) in points.into_iter().enumerate() {
+            match point {
+                Some(point) => points_to_visit.push(point),
+                None => {
+                    let message = format!("Point {} is missing from the floor plan", i);
+                    return Err(ParseError::new(file.lines().count() + 1, 1, "", &message));
+                }
+            }
+        }
+
+        Ok(FloorPlan {
+            data: plan,
+            points_to_visit: points_to_visit,
+        })
+    }
+
+    fn neighbours(&self, &(row, col): &(usize, usize)) -> Vec<(usize, usize)> {
+        let deltas = [(-1i32, 0), (0, 1), (1, 0), (0, -1)];
+
+        deltas.iter()
+            .map(|&(dy, dx)| (row as i32 + dy, col as i32 + dx))
+            .filter(|&(y, x)| y > 0 && (y as usize) < self.data.len() &&
+                x > 0 && (x as usize) < self.data[y as usize].len() &&
+                self.data[y as usize][x as usize])
+            .map(|(y, x)| (y as usize, x as usize))
+            .collect()
+    }
+
+    fn shortest_path_length(&self, from:(usize, usize), to:(usize, usize)) -> Result<usize, ()> {
+        let mut queue = VecDeque::new();
+        let mut visited = HashSet::new();
+        visited.insert(from);
+        queue.push_back(SearchState::new(from, 0));
+
+        while let Some(state) = queue.pop_front() {
+            let neighbours = self.neighbours(&state.current);
+            for neighbour in neighbours {
+                if visited.contains(&neighbour) {
+                    continue;
+                }
+                if neighbour == to {
+                    return Ok(state.length_so_far + 1);
+                }
+
+                visited.insert(neighbour);
+                let new_state = SearchState::new(neighbour, state.length_so_far + 1);
+                queue.push_back(new_state);
+            }
+        }
+        Err(())
+    }
+}
+
+struct SearchState {
+    current: (usize, usize),
+    length_so_far: usize,
+}
+
+impl SearchState {
+    fn new(current:(usize, usize), length: usize) -> SearchState {
+        SearchState{
+            current: current,
+            length_so_far: length
+        }
+    }
+}
+
+fn shortest_route_len(shortest_distances: &HashMap<(usize, usize), usize>, num_points: usize, return_to_start: bool) -> usize {
+    let mut nums:Vec<usize> = (1..num_points).collect();
+
+    let heap:Heap<Vec<usize>, usize> = Heap::new(&mut nums);
+    let shortest_route_len:Option<usize> = heap.map(|sub_perm| {
+        let mut full_perm = sub_perm.clone();
+        full_perm.insert(0, 0);
+        if return_to_start {
+            full_perm.push(0);
+        }
+        full_perm.windows(2).map(|pair| {
+            let a = std::cmp::min(pair[0], pair[1]);
+            let b = std::cmp::max(pair[0], pair[1]);
+            shortest_distances.get(&(a, b)).unwrap()
+        }).sum()
+    }).min();
+
+    shortest_route_len.unwrap()
+}
+
+// Returns the shortest route visiting every point, and the shortest such route returning to 0
+pub fn solve(file: &str) -> Result<(usize, usize), ParseError> {
+    let floor_plan = FloorPlan::new(file)?;
+
+    let num_points = floor_plan.points_to_visit.len();
+
+    let mut shortest_distances = HashMap::new();
+    for i in 0..num_points {
+        for j in (i+1)..num_points {
+            let shortest_dist = floor_plan.shortest_path_length(
+                floor_plan.points_to_visit[i], floor_plan.points_to_visit[j]);
+            match shortest_dist {
+                Ok(sd) => { shortest_distances.insert((i,j), sd); },
+                Err(_) => { panic!("Couldn't find shortest distance between {} and {}", i, j); }
+            }
+        }
+    }
+
+    Ok((
+        shortest_route_len(&shortest_distances, num_points, false),
+        shortest_route_len(&shortest_distances, num_points, true)
+    ))
+}
+
+#[test]
+fn example_shortest_route_is_14() {
+    let file = r"###########
+#0.1.....2#
+#.#######.#
+#4.......3#
+###########";
+    assert_eq!(solve(file).unwrap().0, 14);
+}
+
+#[test]
+fn solves_puzzle_input() {
+    assert_eq!(solve(include_str!("input.txt")), Ok((430, 700)));
+}
+
+#[test]
+fn unexpected_character_is_reported() {
+    let file = r"#####
+#0.1#
+#.x.#
+#####";
+    assert_eq!(solve(file), Err(ParseError::new(3, 3, "x", "Expected '#', '.' or a digit")));
+}
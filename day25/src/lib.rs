@@ -0,0 +1,465 @@
+extern crate assembunny;
+extern crate parse_error;
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use assembunny::{Chain, Cpu, Effect, ExecutionEnvironment, Instruction, MAX_REGISTERS, Operand, StepBudget, optimise, parse};
+#[cfg(test)]
+use assembunny::assemble;
+
+use parse_error::ParseError;
+
+// How to decide that a program is producing a clock signal
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Search {
+    // Skip some outputs, then check some more alternate, and hope that's enough
+    Heuristic{warmup_window: usize, trial_window: usize},
+    // Keep going until the whole machine state repeats, which proves the signal goes on forever.
+    // A program which alternates for more than max_outputs without repeating is given up on.
+    Exact{max_outputs: usize},
+    // Work the answer out from what the program does, falling back to an exact search if it isn't
+    // doing what's expected
+    Symbolic
+}
+
+const MAX_OUTPUTS: usize = 100000;
+
+// How long any one run of the program gets, whether checking the symbolic solver or trying a value
+// of a in a search, before it's given up on
+const MAX_STEPS: usize = 10000000;
+
+// The lowest value of register a which produces a clock signal, along with how often the program's
+// state repeats if that was proven
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Clock {
+    pub initial_a: i64,
+    pub cycle_len: Option<usize>
+}
+
+impl fmt::Display for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.cycle_len {
+            Some(cycle_len) => write!(f, "{} (proven: repeats every {} outputs)", self.initial_a, cycle_len),
+            None => write!(f, "{} (not proven)", self.initial_a)
+        }
+    }
+}
+
+// Why no clock could be found
+#[derive(Debug, PartialEq)]
+pub enum SolveError {
+    Parse(ParseError),
+    // The program toggles its own instructions or reads input, which leaves it with more state than
+    // the exact and symbolic searches can see
+    HiddenState,
+    // Running the program with this value of a didn't show whether it gives a clock signal before
+    // it reached the search's limits on outputs or steps
+    GaveUp(i64)
+}
+
+impl From<ParseError> for SolveError {
+    fn from(error: ParseError) -> SolveError {
+        SolveError::Parse(error)
+    }
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SolveError::Parse(ref error) => write!(f, "{}", error),
+            SolveError::HiddenState => write!(f, "The program toggles instructions or reads input, so only a heuristic search can be used"),
+            SolveError::GaveUp(initial_a) => write!(f, "Gave up on a = {} without finding out whether it gives a clock signal", initial_a)
+        }
+    }
+}
+
+impl Error for SolveError {}
+
+#[derive(Clone)]
+enum ClockSeekingTermType {
+    NotAClock,
+    Clock,
+    // The state after an output repeated, this many outputs after it was first seen
+    Cycle(usize),
+    GaveUp
+}
+
+struct ClockSeekingExecEnv {
+    prev_out: (Option<i64>, Option<i64>),
+    out_count: usize,
+    warmup_window: usize,
+    trial_window: usize
+}
+
+impl ClockSeekingExecEnv {
+    fn new(warmup_window:usize, trial_window:usize) -> ClockSeekingExecEnv {
+        ClockSeekingExecEnv {
+            prev_out: (None, None),
+            out_count: 0,
+            warmup_window: warmup_window,
+            trial_window: trial_window
+        }
+    }
+}
+
+impl ExecutionEnvironment<ClockSeekingTermType> for ClockSeekingExecEnv {
+    fn handle_output(&mut self, val: i64) {
+        self.prev_out = (self.prev_out.1, Some(val));
+        self.out_count += 1;
+    }
+
+    fn should_terminate(&mut self, _: &[i64], _: usize) -> Option<ClockSeekingTermType> {
+        if self.out_count < self.warmup_window {
+            // We're still warming up; don't terminate
+            return None
+        }
+        if self.out_count >= self.warmup_window + self.trial_window {
+            // If we saw enough outputs without an error, it's probably a clock signal
+            return Some(ClockSeekingTermType::Clock)
+        }
+        if let (Some(a), Some(b)) = self.prev_out {
+            if !((a == 0 && b == 1) || (a == 1 && b == 0)) {
+                // Terminate if we're not in an alternating 0 / 1 sequence
+                return Some(ClockSeekingTermType::NotAClock)
+            }
+        }
+
+        // We've not decided either way yet, so don't terminate
+        None
+    }
+}
+
+// Checks every output continues 0, 1, 0, 1... from the very start, and remembers the registers and
+// program counter just after each one. The program can't modify itself or read input, so those
+// (plus the last output) are the whole state of the machine. Seeing one again means the program
+// will go round the same loop forever, producing the same outputs.
+struct CycleFindingExecEnv {
+    last_out: Option<i64>,
+    out_count: usize,
+    new_output: bool,
+    broken: bool,
+    seen: HashMap<(Vec<i64>, usize, Option<i64>), usize>,
+    max_outputs: usize
+}
+
+impl CycleFindingExecEnv {
+    fn new(max_outputs: usize) -> CycleFindingExecEnv {
+        CycleFindingExecEnv {
+            last_out: None,
+            out_count: 0,
+            new_output: false,
+            broken: false,
+            seen: HashMap::new(),
+            max_outputs: max_outputs
+        }
+    }
+}
+
+impl ExecutionEnvironment<ClockSeekingTermType> for CycleFindingExecEnv {
+    fn handle_output(&mut self, val: i64) {
+        let expected = match self.last_out {
+            Some(last) => 1 - last,
+            None => 0
+        };
+        if val != expected {
+            self.broken = true;
+        }
+        self.last_out = Some(val);
+        self.out_count += 1;
+        self.new_output = true;
+    }
+
+    fn should_terminate(&mut self, registers: &[i64], program_counter: usize) -> Option<ClockSeekingTermType> {
+        if self.broken {
+            return Some(ClockSeekingTermType::NotAClock);
+        }
+        if !self.new_output {
+            return None;
+        }
+        self.new_output = false;
+
+        let state = (registers.to_vec(), program_counter, self.last_out);
+        if let Some(&first_seen) = self.seen.get(&state) {
+            return Some(ClockSeekingTermType::Cycle(self.out_count - first_seen));
+        }
+        self.seen.insert(state, self.out_count);
+
+        if self.out_count >= self.max_outputs {
+            Some(ClockSeekingTermType::GaveUp)
+        } else {
+            None
+        }
+    }
+}
+
+// Stops the program with everything it has output once there are `count` outputs, or once it has
+// run for far too long without producing them
+struct CollectOutputs {
+    outputs: Vec<i64>,
+    count: usize,
+    steps: usize
+}
+
+impl ExecutionEnvironment<Vec<i64>> for CollectOutputs {
+    fn handle_output(&mut self, val: i64) {
+        self.outputs.push(val);
+    }
+
+    fn should_terminate(&mut self, _: &[i64], _: usize) -> Option<Vec<i64>> {
+        self.steps += 1;
+        if self.outputs.len() >= self.count || self.steps > MAX_STEPS {
+            Some(self.outputs.clone())
+        } else {
+            None
+        }
+    }
+}
+
+// coefficient * a + constant, where a is the starting value of register a
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Symbolic {
+    coefficient: i64,
+    constant: i64
+}
+
+impl Symbolic {
+    fn constant(constant: i64) -> Symbolic {
+        Symbolic { coefficient: 0, constant: constant }
+    }
+
+    fn value(operand: Operand, regs: &[Symbolic]) -> Symbolic {
+        match operand {
+            Operand::Register(reg_idx) => regs[reg_idx],
+            Operand::Literal(value) => Symbolic::constant(value)
+        }
+    }
+
+    fn known(&self) -> Option<i64> {
+        if self.coefficient == 0 { Some(self.constant) } else { None }
+    }
+}
+
+// Runs the start of the optimised program with a unknown and the other registers 0, for as long as
+// nothing depends on what a is. Returns the registers at that point, in terms of a, or None if
+// something overflows.
+fn symbolic_setup(instructions: &[Instruction]) -> Option<[Symbolic; MAX_REGISTERS]> {
+    let instructions = optimise(instructions);
+    let mut regs = [Symbolic::constant(0); MAX_REGISTERS];
+    regs[0] = Symbolic { coefficient: 1, constant: 0 };
+
+    let mut pc = 0i64;
+    let mut steps = 0;
+    while pc >= 0 && (pc as usize) < instructions.len() {
+        // Jumps which don't depend on a could go round forever
+        steps += 1;
+        if steps > 10000 {
+            return None;
+        }
+
+        match instructions[pc as usize] {
+            Instruction::Copy{source, target: Operand::Register(reg_idx)} => regs[reg_idx] = Symbolic::value(source, &regs),
+            Instruction::Inc{reg: Operand::Register(reg_idx)} => regs[reg_idx].constant = regs[reg_idx].constant.checked_add(1)?,
+            Instruction::Dec{reg: Operand::Register(reg_idx)} => regs[reg_idx].constant = regs[reg_idx].constant.checked_sub(1)?,
+            Instruction::JumpNotZero{check, delta} => {
+                match (Symbolic::value(check, &regs).known(), Symbolic::value(delta, &regs).known()) {
+                    (Some(0), _) => {},
+                    (Some(_), Some(delta)) => pc = pc.saturating_add(delta) - 1,
+                    _ => break
+                }
+            },
+            Instruction::Loop{counter, effects, len} => {
+                let times = match regs[counter].known() {
+                    Some(times) => times,
+                    None => break
+                };
+                let before = regs;
+                for effect in effects.iter() {
+                    match *effect {
+                        Effect::Unchanged => {},
+                        Effect::Add{reg, factor, scale} => {
+                            let factor = Symbolic::value(factor, &before);
+                            let times = times.checked_mul(scale)?;
+                            regs[reg].coefficient = regs[reg].coefficient.checked_add(times.checked_mul(factor.coefficient)?)?;
+                            regs[reg].constant = regs[reg].constant.checked_add(times.checked_mul(factor.constant)?)?;
+                        },
+                        Effect::Set{reg, source} => regs[reg] = Symbolic::value(source, &before)
+                    }
+                }
+                pc += len as i64 - 1;
+            },
+            _ => break
+        }
+        pc += 1;
+    }
+
+    Some(regs)
+}
+
+// The bits of n, lowest first, over and over
+fn repeated_bits(n: i64, count: usize) -> Vec<i64> {
+    let bits:Vec<i64> = (0..64 - n.leading_zeros())
+        .map(|bit| (n >> bit) & 1)
+        .collect();
+    (0..count).map(|i| bits[i % bits.len()]).collect()
+}
+
+// Runs the program from the given a, stopping when the environment decides or giving up after
+// MAX_STEPS, so a program which loops forever without any output can't hang the search
+fn run_search(instructions: &[Instruction], initial_a: i64, exec_env: Box<dyn ExecutionEnvironment<ClockSeekingTermType>>)
+        -> Option<ClockSeekingTermType> {
+    let budget = Box::new(StepBudget::new(MAX_STEPS, ClockSeekingTermType::GaveUp));
+    let mut cpu = Cpu::with_env(&[initial_a, 0, 0, 0], Box::new(Chain::new(vec![exec_env, budget])));
+    // Overflowing doesn't make a clock either
+    cpu.process(instructions.to_vec()).unwrap_or(Some(ClockSeekingTermType::NotAClock))
+}
+
+// Day 25's program adds a constant to a, then outputs the result in binary, lowest bit first, over
+// and over. The setup is worked out symbolically, and the constant is then checked against a few
+// real runs. If it holds, the clock we want comes from the smallest number at least as big as the
+// constant whose binary is 1010...10, and just that a is run to prove it is a clock.
+fn solve_symbolically(instructions: &[Instruction]) -> Option<Clock> {
+    let regs = symbolic_setup(instructions)?;
+
+    let mut constants:Vec<i64> = regs.iter()
+        .filter(|reg| reg.coefficient == 1 && reg.constant > 0)
+        .map(|reg| reg.constant)
+        .collect();
+    constants.sort();
+    constants.dedup();
+
+    for constant in constants {
+        let matches_output = (0..5).all(|a| {
+            let n = a + constant;
+            let count = 2 * (64 - n.leading_zeros()) as usize;
+            let exec_env = CollectOutputs { outputs: vec![], count: count, steps: 0 };
+            let mut cpu = Cpu::with_env(&[a, 0, 0, 0], Box::new(exec_env));
+            cpu.process(instructions.to_vec()) == Ok(Some(repeated_bits(n, count)))
+        });
+        if !matches_output {
+            continue;
+        }
+
+        let mut n:i64 = 2;
+        while n < constant {
+            n = match n.checked_mul(4) {
+                Some(n) => n + 2,
+                None => break
+            };
+        }
+        if n < constant {
+            continue;
+        }
+        let initial_a = n - constant;
+
+        if let Some(ClockSeekingTermType::Cycle(len)) = run_search(instructions, initial_a, Box::new(CycleFindingExecEnv::new(MAX_OUTPUTS))) {
+            return Some(Clock { initial_a: initial_a, cycle_len: Some(len) });
+        }
+    }
+
+    None
+}
+
+// Returns the lowest initial value of register a which produces a clock signal, searching the given
+// way. A program which toggles its own instructions or reads input has more state than the exact
+// and symbolic searches can see, so only the heuristic can be used for it.
+pub fn find_clock(instructions: &[Instruction], search: Search) -> Result<Clock, SolveError> {
+    let hidden_state = instructions.iter().any(|instr| matches!(*instr, Instruction::Toggle{..} | Instruction::In{..}));
+    let search = match search {
+        Search::Exact{..} | Search::Symbolic if hidden_state => return Err(SolveError::HiddenState),
+        Search::Symbolic => match solve_symbolically(instructions) {
+            Some(clock) => return Ok(clock),
+            None => Search::Exact{max_outputs: MAX_OUTPUTS}
+        },
+        search => search
+    };
+
+    for initial_a in 0.. {
+        let exec_env:Box<dyn ExecutionEnvironment<ClockSeekingTermType>> = match search {
+            Search::Heuristic{warmup_window, trial_window} => Box::new(ClockSeekingExecEnv::new(warmup_window, trial_window)),
+            Search::Exact{max_outputs} => Box::new(CycleFindingExecEnv::new(max_outputs)),
+            Search::Symbolic => unreachable!()
+        };
+        match run_search(instructions, initial_a, exec_env) {
+            Some(ClockSeekingTermType::Clock) => return Ok(Clock { initial_a: initial_a, cycle_len: None }),
+            Some(ClockSeekingTermType::Cycle(len)) => return Ok(Clock { initial_a: initial_a, cycle_len: Some(len) }),
+            Some(ClockSeekingTermType::GaveUp) => return Err(SolveError::GaveUp(initial_a)),
+            Some(ClockSeekingTermType::NotAClock) | None => {}
+        }
+    }
+
+    unreachable!("Ran out of initial values without finding a clock")
+}
+
+// Returns the clock signal, worked out directly if possible. There is no part 2
+pub fn solve(file: &str) -> Result<(Clock, &'static str), SolveError> {
+    let instructions = parse(file)?;
+
+    Ok((find_clock(&instructions, Search::Symbolic)?, "N/A"))
+}
+
+#[test]
+fn solves_puzzle_input() {
+    assert_eq!(solve(include_str!("input.txt")), Ok((Clock { initial_a: 158, cycle_len: Some(12) }, "N/A")));
+}
+
+#[test]
+fn symbolic_setup_finds_the_added_constant() {
+    let regs = symbolic_setup(&parse(include_str!("input.txt")).unwrap()).unwrap();
+    assert_eq!(regs[3], Symbolic { coefficient: 1, constant: 4 * 643 });
+    assert_eq!(solve_symbolically(&parse(include_str!("input.txt")).unwrap()), Some(Clock { initial_a: 158, cycle_len: Some(12) }));
+}
+
+#[test]
+fn exact_search_agrees_with_symbolic_solver() {
+    let instructions = parse(include_str!("input.txt")).unwrap();
+    assert_eq!(find_clock(&instructions, Search::Exact{max_outputs: MAX_OUTPUTS}), Ok(Clock { initial_a: 158, cycle_len: Some(12) }));
+}
+
+#[test]
+fn heuristic_agrees_with_exact_search() {
+    let instructions = parse(include_str!("input.txt")).unwrap();
+    let clock = find_clock(&instructions, Search::Heuristic{warmup_window: 10, trial_window: 32});
+    assert_eq!(clock, Ok(Clock { initial_a: 158, cycle_len: None }));
+}
+
+#[test]
+fn exact_search_needs_the_stream_to_start_with_0() {
+    let program = r"
+        jnz a zero_first
+        out 1               ; a is 0, so start 1, 0, 1, 0...
+zero_first:
+        out 0
+        out 1
+        jnz 1 zero_first";
+    let instructions = assemble(program).unwrap();
+    assert_eq!(find_clock(&instructions, Search::Exact{max_outputs: 100}), Ok(Clock { initial_a: 1, cycle_len: Some(2) }));
+    assert_eq!(solve_symbolically(&instructions), None);
+    assert_eq!(find_clock(&instructions, Search::Symbolic), Ok(Clock { initial_a: 1, cycle_len: Some(2) }));
+    assert_eq!(find_clock(&instructions, Search::Heuristic{warmup_window: 10, trial_window: 32}).unwrap().initial_a, 0);
+}
+
+#[test]
+fn only_the_heuristic_searches_a_program_which_toggles() {
+    // The tgl toggles itself into an inc, and then the rest is a plain clock
+    let instructions = parse("tgl c\nout 0\nout 1\njnz 1 -2").unwrap();
+    assert_eq!(find_clock(&instructions, Search::Exact{max_outputs: 100}), Err(SolveError::HiddenState));
+    assert_eq!(find_clock(&instructions, Search::Symbolic), Err(SolveError::HiddenState));
+    assert_eq!(find_clock(&instructions, Search::Heuristic{warmup_window: 10, trial_window: 32}),
+               Ok(Clock { initial_a: 0, cycle_len: None }));
+}
+
+#[test]
+fn searches_give_up_on_a_program_which_never_outputs() {
+    let instructions = parse("jnz 1 0").unwrap();
+    assert_eq!(find_clock(&instructions, Search::Exact{max_outputs: 100}), Err(SolveError::GaveUp(0)));
+    assert_eq!(find_clock(&instructions, Search::Heuristic{warmup_window: 10, trial_window: 32}), Err(SolveError::GaveUp(0)));
+}
+
+#[test]
+fn exact_search_gives_up_on_a_clock_which_never_repeats() {
+    // b counts up between outputs, so the state never repeats
+    let instructions = assemble("loop: inc b\n      out 0\n      out 1\n      jnz 1 loop").unwrap();
+    assert_eq!(find_clock(&instructions, Search::Exact{max_outputs: 100}), Err(SolveError::GaveUp(0)));
+}
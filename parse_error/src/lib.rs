@@ -0,0 +1,58 @@
+use std::error::Error;
+use std::fmt;
+
+// A problem with the puzzle input, pointing at the text which couldn't be understood.
+// Lines and columns count from 1, columns in characters rather than bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub text: String,
+    pub message: String
+}
+
+impl ParseError {
+    pub fn new(line: usize, column: usize, text: &str, message: &str) -> ParseError {
+        ParseError {
+            line: line,
+            column: column,
+            text: text.to_string(),
+            message: message.to_string()
+        }
+    }
+
+    // Works out the column from where the offending text sits in the line. The offending text
+    // should be a slice of the line (e.g. a regex capture), otherwise its first occurrence is used.
+    pub fn at(line: usize, line_text: &str, offending: &str, message: &str) -> ParseError {
+        let start = line_text.as_ptr() as usize;
+        let offending_start = offending.as_ptr() as usize;
+
+        let byte_offset = if offending_start >= start && offending_start + offending.len() <= start + line_text.len() {
+            Some(offending_start - start)
+        } else {
+            line_text.find(offending)
+        };
+        let column = match byte_offset {
+            Some(offset) => line_text[..offset].chars().count() + 1,
+            None => 1
+        };
+
+        ParseError::new(line, column, offending, message)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}: {}: '{}'", self.line, self.column, self.message, self.text)
+    }
+}
+
+impl Error for ParseError {}
+
+#[test]
+fn column_comes_from_position_of_slice_in_line() {
+    let line = "cpy 1 1 e";
+    let error = ParseError::at(3, line, &line[8..], "Unexpected operand");
+    assert_eq!(error, ParseError::new(3, 9, "e", "Unexpected operand"));
+    assert_eq!(error.to_string(), "line 3, column 9: Unexpected operand: 'e'");
+}